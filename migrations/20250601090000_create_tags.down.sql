@@ -0,0 +1,3 @@
+DROP TABLE media_tags;
+
+DROP TABLE tags;
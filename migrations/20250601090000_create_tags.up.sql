@@ -0,0 +1,25 @@
+CREATE TABLE tags (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    user_id integer NOT NULL REFERENCES users(id),
+    name varchar NOT NULL,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX tags_user_id_name_key ON tags (user_id, lower(name))
+WHERE
+    deleted_at IS NULL;
+
+CREATE TABLE media_tags (
+    media_id integer NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+    tag_id integer NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    created_by integer REFERENCES users(id),
+    PRIMARY KEY (media_id, tag_id)
+);
+
+CREATE INDEX media_tags_tag_id_idx ON media_tags (tag_id);
@@ -0,0 +1,6 @@
+DROP INDEX media_user_id_favorite_idx;
+
+ALTER TABLE media
+    DROP COLUMN favorite,
+    DROP COLUMN rating,
+    DROP COLUMN archived;
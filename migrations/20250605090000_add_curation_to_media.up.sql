@@ -0,0 +1,6 @@
+ALTER TABLE media
+    ADD COLUMN favorite boolean NOT NULL DEFAULT FALSE,
+    ADD COLUMN rating smallint NOT NULL DEFAULT 0 CHECK (rating BETWEEN 0 AND 5),
+    ADD COLUMN archived boolean NOT NULL DEFAULT FALSE;
+
+CREATE INDEX media_user_id_favorite_idx ON media (user_id) WHERE favorite;
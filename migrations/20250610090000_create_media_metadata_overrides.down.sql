@@ -0,0 +1,3 @@
+DROP TABLE media_metadata_audit;
+
+DROP TABLE media_metadata_overrides;
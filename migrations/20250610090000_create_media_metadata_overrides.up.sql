@@ -0,0 +1,29 @@
+CREATE TABLE media_metadata_overrides (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    media_id integer NOT NULL UNIQUE REFERENCES media(id) ON DELETE CASCADE,
+    title varchar,
+    description text,
+    taken_at timestamp,
+    taken_at_offset varchar,
+    latitude double precision,
+    longitude double precision,
+    location_name varchar,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id)
+);
+
+CREATE TABLE media_metadata_audit (
+    id serial PRIMARY KEY NOT NULL,
+    media_id integer NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+    field varchar NOT NULL,
+    old_value jsonb,
+    new_value jsonb,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    created_by integer REFERENCES users(id)
+);
+
+CREATE INDEX media_metadata_audit_media_id_idx ON media_metadata_audit (media_id);
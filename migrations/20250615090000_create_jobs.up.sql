@@ -0,0 +1,21 @@
+CREATE TABLE jobs (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    user_id integer NOT NULL REFERENCES users(id),
+    job_type integer NOT NULL,
+    status integer NOT NULL DEFAULT 0,
+    payload jsonb,
+    result jsonb,
+    progress integer NOT NULL DEFAULT 0,
+    total integer NOT NULL DEFAULT 0,
+    error text,
+    started_at timestamp WITH time zone,
+    finished_at timestamp WITH time zone,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id)
+);
+
+CREATE INDEX jobs_user_id_idx ON jobs (user_id);
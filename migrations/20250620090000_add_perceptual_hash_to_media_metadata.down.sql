@@ -0,0 +1,2 @@
+ALTER TABLE media_metadata
+    DROP COLUMN perceptual_hash;
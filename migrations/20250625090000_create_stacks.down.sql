@@ -0,0 +1,4 @@
+ALTER TABLE media
+    DROP COLUMN stack_id;
+
+DROP TABLE stacks;
@@ -0,0 +1,17 @@
+CREATE TABLE stacks (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    user_id integer NOT NULL REFERENCES users(id),
+    primary_media_id integer NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+    stack_type integer NOT NULL DEFAULT 0,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id)
+);
+
+ALTER TABLE media
+    ADD COLUMN stack_id integer REFERENCES stacks(id) ON DELETE SET NULL;
+
+CREATE INDEX media_stack_id_idx ON media (stack_id);
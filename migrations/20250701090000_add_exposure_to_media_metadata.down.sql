@@ -0,0 +1,4 @@
+ALTER TABLE media_metadata
+    DROP COLUMN lens_model,
+    DROP COLUMN iso,
+    DROP COLUMN exposure_time;
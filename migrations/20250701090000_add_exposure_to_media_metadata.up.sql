@@ -0,0 +1,4 @@
+ALTER TABLE media_metadata
+    ADD COLUMN lens_model varchar,
+    ADD COLUMN iso integer,
+    ADD COLUMN exposure_time varchar;
@@ -0,0 +1,10 @@
+DROP INDEX media_metadata_content_identifier_idx;
+
+ALTER TABLE media_metadata
+    DROP COLUMN content_identifier,
+    DROP COLUMN motion_video_length;
+
+DROP INDEX media_motion_media_id_idx;
+
+ALTER TABLE media
+    DROP COLUMN motion_media_id;
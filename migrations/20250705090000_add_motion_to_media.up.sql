@@ -0,0 +1,10 @@
+ALTER TABLE media
+    ADD COLUMN motion_media_id integer REFERENCES media(id) ON DELETE SET NULL;
+
+CREATE INDEX media_motion_media_id_idx ON media (motion_media_id);
+
+ALTER TABLE media_metadata
+    ADD COLUMN content_identifier varchar,
+    ADD COLUMN motion_video_length bigint;
+
+CREATE INDEX media_metadata_content_identifier_idx ON media_metadata (content_identifier);
@@ -0,0 +1,2 @@
+ALTER TABLE media_metadata
+    ADD COLUMN page_count integer;
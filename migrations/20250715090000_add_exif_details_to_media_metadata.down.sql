@@ -0,0 +1,10 @@
+DROP INDEX media_metadata_iso_idx;
+DROP INDEX media_metadata_lens_model_idx;
+
+ALTER TABLE media_metadata
+    DROP COLUMN orientation,
+    DROP COLUMN taken_at_offset,
+    DROP COLUMN flash,
+    DROP COLUMN white_balance,
+    DROP COLUMN software,
+    DROP COLUMN exif;
@@ -0,0 +1,10 @@
+ALTER TABLE media_metadata
+    ADD COLUMN orientation smallint,
+    ADD COLUMN taken_at_offset varchar,
+    ADD COLUMN flash varchar,
+    ADD COLUMN white_balance varchar,
+    ADD COLUMN software varchar,
+    ADD COLUMN exif jsonb;
+
+CREATE INDEX media_metadata_lens_model_idx ON media_metadata (lower(lens_model));
+CREATE INDEX media_metadata_iso_idx ON media_metadata (iso);
@@ -0,0 +1,6 @@
+ALTER TABLE media_metadata
+    DROP COLUMN title,
+    DROP COLUMN description,
+    DROP COLUMN keywords,
+    DROP COLUMN rating,
+    DROP COLUMN regions;
@@ -0,0 +1,6 @@
+ALTER TABLE media_metadata
+    ADD COLUMN title varchar,
+    ADD COLUMN description text,
+    ADD COLUMN keywords varchar[],
+    ADD COLUMN rating smallint,
+    ADD COLUMN regions jsonb;
@@ -0,0 +1,3 @@
+ALTER TABLE media_metadata
+    DROP COLUMN display_width,
+    DROP COLUMN display_height;
@@ -0,0 +1,8 @@
+ALTER TABLE media_metadata
+    ADD COLUMN display_width integer,
+    ADD COLUMN display_height integer;
+
+UPDATE media_metadata
+SET
+    display_width = CASE WHEN orientation BETWEEN 5 AND 8 THEN height ELSE width END,
+    display_height = CASE WHEN orientation BETWEEN 5 AND 8 THEN width ELSE height END;
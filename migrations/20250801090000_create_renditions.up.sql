@@ -0,0 +1,43 @@
+CREATE TABLE rendition_presets (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    name varchar NOT NULL,
+    max_size integer NOT NULL CHECK (max_size > 0),
+    square boolean NOT NULL DEFAULT FALSE,
+    format varchar NOT NULL DEFAULT 'webp' CHECK (format IN ('webp', 'jpeg')),
+    generate_on_ingest boolean NOT NULL DEFAULT TRUE,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX rendition_presets_name_key ON rendition_presets (name)
+WHERE
+    deleted_at IS NULL;
+
+INSERT INTO rendition_presets (name, max_size, square, format, generate_on_ingest)
+VALUES
+    ('small', 250, TRUE, 'webp', TRUE),
+    ('grid', 400, FALSE, 'webp', TRUE),
+    ('preview', 1440, FALSE, 'jpeg', TRUE),
+    ('fullscreen', 2560, FALSE, 'jpeg', FALSE);
+
+CREATE TABLE media_renditions (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    media_id integer NOT NULL REFERENCES media(id) ON DELETE CASCADE,
+    preset_id integer NOT NULL REFERENCES rendition_presets(id) ON DELETE CASCADE,
+    filepath varchar NOT NULL,
+    content_type varchar NOT NULL,
+    width integer,
+    height integer,
+    size bigint,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id),
+    UNIQUE (media_id, preset_id)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE media_streams (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    media_id integer NOT NULL UNIQUE REFERENCES media(id) ON DELETE CASCADE,
+    status integer NOT NULL DEFAULT 0,
+    directory varchar,
+    variants jsonb,
+    error text,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id)
+);
@@ -0,0 +1,35 @@
+CREATE TABLE watched_folders (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    user_id integer NOT NULL REFERENCES users(id),
+    path varchar NOT NULL,
+    mode integer NOT NULL DEFAULT 1,
+    enabled boolean NOT NULL DEFAULT true,
+    last_scanned_at timestamp WITH time zone,
+    error text,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id)
+);
+
+CREATE UNIQUE INDEX watched_folders_user_id_path_idx ON watched_folders (user_id, path) WHERE deleted_at IS NULL;
+
+CREATE TABLE watched_files (
+    id serial PRIMARY KEY NOT NULL,
+    uuid uuid NOT NULL DEFAULT uuid_generate_v4(),
+    watched_folder_id integer NOT NULL REFERENCES watched_folders(id) ON DELETE CASCADE,
+    path varchar NOT NULL,
+    size bigint NOT NULL,
+    modified_at timestamp WITH time zone NOT NULL,
+    status integer NOT NULL DEFAULT 0,
+    media_id integer REFERENCES media(id) ON DELETE SET NULL,
+    error text,
+    created_at timestamp WITH time zone DEFAULT NOW(),
+    updated_at timestamp WITH time zone DEFAULT NOW(),
+    deleted_at timestamp WITH time zone,
+    created_by integer REFERENCES users(id),
+    updated_by integer REFERENCES users(id),
+    UNIQUE (watched_folder_id, path)
+);
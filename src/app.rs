@@ -1,16 +1,27 @@
 use axum::Router;
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use std::sync::Arc;
+use tokio::sync::Notify;
 use tower_http::cors::CorsLayer;
 
 use crate::auth::routes::auth_routes;
+use crate::export::routes::export_routes;
+use crate::import::routes::import_routes;
+use crate::import::services::WatchService;
+use crate::job::routes::job_routes;
+use crate::job::services::JobService;
 use crate::media::routes::media_routes;
+use crate::media::services::HlsService;
+use crate::stack::routes::stack_routes;
+use crate::tag::routes::tag_routes;
 use crate::test::routes::test_routes;
 use crate::user::routes::user_routes;
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub db: PgPool,
+    /// Wakes the folder watcher after an admin changes the watched folders.
+    pub import_watch: Arc<Notify>,
 }
 
 pub async fn create_app() -> Router {
@@ -30,7 +41,23 @@ pub async fn create_app() -> Router {
         }
     };
 
-    let app_state = Arc::new(AppState { db: pool.clone() });
+    if let Err(err) = JobService::fail_interrupted_jobs(&pool).await {
+        println!("Failed to reset interrupted jobs: {:?}", err);
+    }
+
+    if let Err(err) = HlsService::fail_interrupted_streams(&pool).await {
+        println!("Failed to reset interrupted streams: {:?}", err);
+    }
+
+    let app_state = Arc::new(AppState {
+        db: pool.clone(),
+        import_watch: Arc::new(Notify::new()),
+    });
+
+    tokio::spawn(WatchService::run(
+        pool.clone(),
+        app_state.import_watch.clone(),
+    ));
 
     // let frontend_origin =
     //     std::env::var("FRONTEND_ORIGIN").unwrap_or("http://localhost:3000".to_string());
@@ -48,5 +75,10 @@ pub async fn create_app() -> Router {
         .merge(auth_routes(app_state.clone()))
         .merge(user_routes(app_state.clone()))
         .merge(media_routes(app_state.clone()))
+        .merge(tag_routes(app_state.clone()))
+        .merge(stack_routes(app_state.clone()))
+        .merge(job_routes(app_state.clone()))
+        .merge(export_routes(app_state.clone()))
+        .merge(import_routes(app_state.clone()))
         .layer(cors)
 }
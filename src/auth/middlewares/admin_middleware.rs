@@ -0,0 +1,24 @@
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+
+use crate::config::get_admin_emails;
+use crate::errors::app_error::AppError;
+use crate::user::models::UserModel;
+
+/// Lets through users listed in `ADMIN_EMAILS`. Runs after `auth_middleware`, which
+/// provides the user.
+pub async fn admin_middleware(
+    Extension(user): Extension<UserModel>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if get_admin_emails().contains(&user.email.to_lowercase()) {
+        return next.run(req).await;
+    }
+
+    AppError::Forbidden("Admin access required".into()).into_response()
+}
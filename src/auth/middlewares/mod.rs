@@ -1,5 +1,7 @@
+pub mod admin_middleware;
 pub mod auth_middleware;
 pub mod refresh_token_middleware;
 
+pub use admin_middleware::*;
 pub use auth_middleware::*;
 pub use refresh_token_middleware::*;
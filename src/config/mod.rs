@@ -28,3 +28,66 @@ pub fn get_refresh_token_expiry() -> i64 {
         .parse::<i64>()
         .unwrap_or(default_value)
 }
+
+pub fn get_image_cache_max_bytes() -> u64 {
+    dotenv().ok();
+
+    let default_value = 1024 * 1024 * 1024;
+
+    env::var("IMAGE_CACHE_MAX_BYTES")
+        .unwrap_or_else(|_| format!("{}", default_value))
+        .parse::<u64>()
+        .unwrap_or(default_value)
+}
+
+pub fn get_export_part_max_bytes() -> u64 {
+    dotenv().ok();
+
+    let default_value = 2 * 1024 * 1024 * 1024;
+
+    env::var("EXPORT_PART_MAX_BYTES")
+        .unwrap_or_else(|_| format!("{}", default_value))
+        .parse::<u64>()
+        .unwrap_or(default_value)
+}
+
+pub fn get_import_dir() -> String {
+    dotenv().ok();
+
+    env::var("IMPORT_DIR").unwrap_or_else(|_| "./imports".to_string())
+}
+
+/// Comma-separated emails of the users allowed to administer the server.
+pub fn get_admin_emails() -> Vec<String> {
+    dotenv().ok();
+
+    env::var("ADMIN_EMAILS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|email| email.trim().to_lowercase())
+        .filter(|email| !email.is_empty())
+        .collect()
+}
+
+pub fn get_watch_rescan_seconds() -> u64 {
+    dotenv().ok();
+
+    let default_value = 5 * 60;
+
+    env::var("WATCH_RESCAN_SECONDS")
+        .unwrap_or_else(|_| format!("{}", default_value))
+        .parse::<u64>()
+        .unwrap_or(default_value)
+        .max(1)
+}
+
+pub fn get_watch_settle_seconds() -> u64 {
+    dotenv().ok();
+
+    let default_value = 30;
+
+    env::var("WATCH_SETTLE_SECONDS")
+        .unwrap_or_else(|_| format!("{}", default_value))
+        .parse::<u64>()
+        .unwrap_or(default_value)
+}
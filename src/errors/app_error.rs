@@ -6,6 +6,7 @@ use crate::errors::error_response_dto::ErrorResponseDto;
 pub enum AppError {
     BadRequest(String),
     Unauthorized(String),
+    Forbidden(String),
     NotFound(String),
     InternalServerError(String),
     EndOfFile,
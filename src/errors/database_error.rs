@@ -0,0 +1,7 @@
+/// Whether a query failed on a unique constraint, e.g. a name that is already taken.
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "23505")
+}
@@ -40,6 +40,7 @@ impl From<AppError> for ErrorResponseDto {
             AppError::Unauthorized(message) => {
                 ErrorResponseDto::new(StatusCode::UNAUTHORIZED, message)
             }
+            AppError::Forbidden(message) => ErrorResponseDto::new(StatusCode::FORBIDDEN, message),
             AppError::NotFound(message) => ErrorResponseDto::new(StatusCode::NOT_FOUND, message),
             AppError::InternalServerError(message) => {
                 ErrorResponseDto::new(StatusCode::INTERNAL_SERVER_ERROR, message)
@@ -1,2 +1,3 @@
 pub mod app_error;
+pub mod database_error;
 pub mod error_response_dto;
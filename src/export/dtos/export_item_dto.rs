@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::media::{
+    enums::media_type_enum::MediaTypeEnum,
+    models::{MediaMetadataAuditModel, MediaMetadataModel, MediaMetadataOverrideModel},
+};
+
+/// `media/{uuid}/metadata.json`: everything known about one item, with references to
+/// other items, tags and stacks made by UUID so they survive a move between servers.
+#[derive(Serialize)]
+pub struct ExportItemDto {
+    pub uuid: Uuid,
+    pub media_type: MediaTypeEnum,
+    /// Archive path of the original, or None when the file was missing on export.
+    pub original: Option<String>,
+    pub original_filename: Option<String>,
+    pub sidecars: Vec<String>,
+    pub favorite: bool,
+    pub rating: i16,
+    pub archived: bool,
+    pub stack_uuid: Option<Uuid>,
+    pub motion_media_uuid: Option<Uuid>,
+    pub tags: Vec<ExportTagDto>,
+    /// As extracted from the file.
+    pub metadata: Option<MediaMetadataModel>,
+    /// User edits, which take precedence over `metadata`.
+    pub overrides: Option<MediaMetadataOverrideModel>,
+    /// Every recorded edit, newest first.
+    pub history: Vec<MediaMetadataAuditModel>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ExportTagDto {
+    pub uuid: Uuid,
+    pub name: String,
+}
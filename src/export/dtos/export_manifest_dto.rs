@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::export::dtos::ExportTagDto;
+use crate::stack::enums::stack_type_enum::StackTypeEnum;
+
+/// `manifest.json`, the last entry of the last part.
+#[derive(Serialize)]
+pub struct ExportManifestDto {
+    pub format: &'static str,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub account: ExportAccountDto,
+    pub part_count: u32,
+    pub tags: Vec<ExportTagDto>,
+    pub stacks: Vec<ExportStackDto>,
+    pub items: Vec<ExportManifestItemDto>,
+}
+
+#[derive(Serialize)]
+pub struct ExportAccountDto {
+    pub uuid: Uuid,
+    pub email: String,
+    pub username: String,
+}
+
+#[derive(Serialize)]
+pub struct ExportStackDto {
+    pub uuid: Uuid,
+    pub stack_type: StackTypeEnum,
+    pub primary_media_uuid: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct ExportManifestItemDto {
+    pub uuid: Uuid,
+    pub part: u32,
+    pub metadata: String,
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// Result of a finished export job, listing the parts it can be downloaded in.
+#[derive(Serialize, Deserialize)]
+pub struct ExportResultDto {
+    pub format_version: u32,
+    pub items: usize,
+    pub parts: Vec<ExportPartDto>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportPartDto {
+    pub part: u32,
+    pub filename: String,
+    pub size: u64,
+    pub items: usize,
+}
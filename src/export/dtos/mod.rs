@@ -0,0 +1,7 @@
+pub mod export_item_dto;
+pub mod export_manifest_dto;
+pub mod export_result_dto;
+
+pub use export_item_dto::*;
+pub use export_manifest_dto::*;
+pub use export_result_dto::*;
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Path, State},
+    response::Response,
+    Extension, Json,
+};
+use hyper::{
+    header::{HeaderValue, CONTENT_DISPOSITION},
+    HeaderMap, Method,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::errors::app_error::AppError;
+use crate::export::services::ExportService;
+use crate::job::{models::JobModel, services::JobService};
+use crate::media::services::DownloadService;
+use crate::user::models::UserModel;
+
+pub async fn create_export(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+) -> Result<Json<JobModel>, AppError> {
+    let job = ExportService::request_export(&state.db, &user).await?;
+
+    Ok(Json(job))
+}
+
+pub async fn download_export_part(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path((id, part)): Path<(i32, u32)>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let job = JobService::find_job(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .ok_or_else(|| AppError::NotFound("Export not found".into()))?;
+
+    let (filepath, filename) = ExportService::find_part(&job, part)
+        .ok_or_else(|| AppError::NotFound("Export not found".into()))?;
+
+    let mut response =
+        DownloadService::stream_file(&filepath, "application/zip", &method, &headers).await?;
+
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename))
+        .map_err(|_| AppError::InternalServerError("Failed to build response".into()))?;
+    response
+        .headers_mut()
+        .insert(CONTENT_DISPOSITION, disposition);
+
+    Ok(response)
+}
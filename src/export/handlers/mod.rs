@@ -0,0 +1,3 @@
+pub mod export_handler;
+
+pub use export_handler::*;
@@ -0,0 +1,4 @@
+pub mod dtos;
+pub mod handlers;
+pub mod routes;
+pub mod services;
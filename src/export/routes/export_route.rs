@@ -0,0 +1,21 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::auth::middlewares::auth_middleware;
+use crate::export::handlers::{create_export, download_export_part};
+
+pub fn export_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/exports", post(create_export))
+        .route("/exports/{id}/parts/{part}", get(download_export_part))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state)
+}
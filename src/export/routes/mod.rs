@@ -0,0 +1,3 @@
+pub mod export_route;
+
+pub use export_route::*;
@@ -0,0 +1,437 @@
+use chrono::{NaiveDateTime, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{collections::HashMap, path::Path};
+use tokio::{
+    fs::{self, File},
+    io::BufWriter,
+};
+use uuid::Uuid;
+
+use crate::config::get_export_part_max_bytes;
+use crate::errors::app_error::AppError;
+use crate::export::dtos::{
+    ExportAccountDto, ExportItemDto, ExportManifestDto, ExportManifestItemDto, ExportPartDto,
+    ExportResultDto, ExportStackDto, ExportTagDto,
+};
+use crate::job::{
+    enums::{job_status_enum::JobStatusEnum, job_type_enum::JobTypeEnum},
+    models::JobModel,
+    services::JobService,
+};
+use crate::media::{
+    models::MediaModel,
+    services::{
+        ArchiveWriter, FileService, MediaMetadataOverrideService, MediaMetadataService,
+        MediaService, XmpService,
+    },
+};
+use crate::stack::services::StackService;
+use crate::tag::services::TagService;
+use crate::user::models::UserModel;
+
+pub const EXPORT_FORMAT: &str = "photo-backup-export";
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+const EXPORT_DIR: &str = "./exports";
+const EXPORT_MANIFEST: &str = "manifest.json";
+/// Allowance per archive entry for its headers, on top of the name and data.
+const ENTRY_OVERHEAD: u64 = 128;
+
+type PartWriter = ArchiveWriter<BufWriter<File>>;
+
+/// Exports a whole account as one or more ZIP parts in this layout (format version 1):
+///
+/// ```text
+/// media/{uuid}/original/{original filename}
+/// media/{uuid}/sidecars/{sidecar filename}
+/// media/{uuid}/metadata.json
+/// manifest.json
+/// ```
+///
+/// Each item lives entirely in one part with its `metadata.json` (see `ExportItemDto`),
+/// so every part can be imported on its own. Tags and stacks are referenced by UUID and
+/// described in `manifest.json` (see `ExportManifestDto`), which closes the last part
+/// and lists which part holds each item. Parts are cut at `EXPORT_PART_MAX_BYTES`
+/// unless a single item is larger.
+pub struct ExportService {}
+
+impl ExportService {
+    /// Queues an export unless one is already pending or running for this user.
+    pub async fn request_export(pool: &PgPool, user: &UserModel) -> Result<JobModel, AppError> {
+        let payload = json!({ "format_version": EXPORT_FORMAT_VERSION });
+
+        if let Some(job) =
+            JobService::find_active_job(pool, user.id, JobTypeEnum::ExportAccount, &payload)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        {
+            return Ok(job);
+        }
+
+        let job = JobService::create_job(pool, user, JobTypeEnum::ExportAccount, Some(payload))
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let task_pool = pool.clone();
+        let task_user = user.clone();
+        let job_id = job.id;
+        let job_uuid = job.uuid;
+
+        JobService::spawn(pool.clone(), user.clone(), &job, async move {
+            let directory = Self::export_dir(&job_uuid);
+            let result = Self::export_account(&task_pool, job_id, &task_user, &directory).await;
+
+            match result {
+                Ok(_) => Self::remove_previous_exports(&task_pool, task_user.id, job_id).await,
+                Err(_) => {
+                    let _ = fs::remove_dir_all(&directory).await;
+                }
+            }
+
+            result
+        });
+
+        Ok(job)
+    }
+
+    async fn export_account(
+        pool: &PgPool,
+        job_id: i32,
+        user: &UserModel,
+        directory: &str,
+    ) -> Result<Option<serde_json::Value>, AppError> {
+        let media = MediaService::list_user_media(pool, user.id, None)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        JobService::mark_running(pool, job_id, media.len() as i32)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let tags = TagService::list_tags(pool, user.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let stacks = StackService::list_stacks(pool, user.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let media_uuids: HashMap<i32, Uuid> = media.iter().map(|m| (m.id, m.uuid)).collect();
+        let stack_uuids: HashMap<i32, Uuid> = stacks.iter().map(|s| (s.id, s.uuid)).collect();
+
+        fs::create_dir_all(directory)
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to create export".into()))?;
+
+        let max_bytes = get_export_part_max_bytes();
+        let exported_on = Utc::now().format("%Y%m%d").to_string();
+
+        let mut parts = Vec::new();
+        let mut items = Vec::with_capacity(media.len());
+        let mut part_items = 0;
+        let mut archive = Self::open_part(directory, 1).await?;
+
+        for (index, item) in media.iter().enumerate() {
+            let export = Self::collect_item(pool, item, &media_uuids, &stack_uuids).await?;
+
+            let estimate = export.files.iter().map(|(_, _, size)| size).sum::<u64>()
+                + export.json.len() as u64
+                + (export.files.len() as u64 + 1) * ENTRY_OVERHEAD;
+
+            // An item never spans parts, so one larger than a part gets a part of its own.
+            if !archive.is_empty() && archive.len() + estimate > max_bytes {
+                parts.push(
+                    Self::close_part(directory, archive, parts.len() as u32 + 1, part_items)
+                        .await?,
+                );
+
+                archive = Self::open_part(directory, parts.len() as u32 + 1).await?;
+                part_items = 0;
+            }
+
+            for (name, filepath, _) in &export.files {
+                let mut file = File::open(filepath)
+                    .await
+                    .map_err(|_| AppError::InternalServerError("Failed to read media".into()))?;
+
+                archive
+                    .add_file(name, &mut file, export.modified)
+                    .await
+                    .map_err(|_| AppError::InternalServerError("Failed to write export".into()))?;
+            }
+
+            let metadata_path = format!("media/{}/metadata.json", item.uuid);
+
+            archive
+                .add_bytes(&metadata_path, &export.json, export.modified)
+                .await
+                .map_err(|_| AppError::InternalServerError("Failed to write export".into()))?;
+
+            items.push(ExportManifestItemDto {
+                uuid: item.uuid,
+                part: parts.len() as u32 + 1,
+                metadata: metadata_path,
+            });
+            part_items += 1;
+
+            let _ = JobService::update_progress(pool, job_id, index as i32 + 1).await;
+        }
+
+        let manifest = ExportManifestDto {
+            format: EXPORT_FORMAT,
+            version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            account: ExportAccountDto {
+                uuid: user.uuid,
+                email: user.email.clone(),
+                username: user.username.clone(),
+            },
+            part_count: parts.len() as u32 + 1,
+            tags: tags
+                .iter()
+                .map(|t| ExportTagDto {
+                    uuid: t.uuid,
+                    name: t.name.clone(),
+                })
+                .collect(),
+            stacks: stacks
+                .iter()
+                .map(|s| ExportStackDto {
+                    uuid: s.uuid,
+                    stack_type: s.stack_type.clone(),
+                    primary_media_uuid: media_uuids.get(&s.primary_media_id).copied(),
+                })
+                .collect(),
+            items,
+        };
+
+        let manifest = serde_json::to_vec_pretty(&manifest)
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        archive
+            .add_bytes(EXPORT_MANIFEST, &manifest, Utc::now().naive_utc())
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to write export".into()))?;
+
+        parts.push(Self::close_part(directory, archive, parts.len() as u32 + 1, part_items).await?);
+
+        let part_count = parts.len();
+
+        for part in parts.iter_mut() {
+            part.filename = format!(
+                "photo-export-{}-part-{}-of-{}.zip",
+                exported_on, part.part, part_count
+            );
+        }
+
+        let result = ExportResultDto {
+            format_version: EXPORT_FORMAT_VERSION,
+            items: media.len(),
+            parts,
+        };
+
+        serde_json::to_value(&result)
+            .map(Some)
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))
+    }
+
+    /// Gathers the files and `metadata.json` of one item. A missing original is left
+    /// out of the archive, but its metadata is still exported.
+    async fn collect_item(
+        pool: &PgPool,
+        media: &MediaModel,
+        media_uuids: &HashMap<i32, Uuid>,
+        stack_uuids: &HashMap<i32, Uuid>,
+    ) -> Result<ExportItem, AppError> {
+        let metadata = MediaMetadataService::get_metadata_for_media(pool, media.id)
+            .await
+            .ok();
+
+        let overrides = MediaMetadataOverrideService::get_overrides_for_media(pool, media.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let history = MediaMetadataOverrideService::get_audit_for_media(pool, media.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let tags = TagService::tags_for_media(pool, media.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let original_filename = metadata.as_ref().and_then(|m| m.original_filename.clone());
+        let filename = original_filename.as_deref().unwrap_or(&media.filename);
+
+        let mut files = Vec::new();
+        let mut original = None;
+        let mut sidecars = Vec::new();
+
+        match fs::metadata(&media.filepath).await {
+            Ok(file) => {
+                let name = format!(
+                    "media/{}/original/{}",
+                    media.uuid,
+                    FileService::clean_filename(filename)
+                );
+
+                files.push((name.clone(), media.filepath.clone(), file.len()));
+                original = Some(name);
+            }
+            Err(e) => eprintln!("Exporting media {} without its original: {}", media.id, e),
+        }
+
+        if let Some(sidecar) = XmpService::find_sidecar(&media.filepath) {
+            if let Ok(file) = fs::metadata(&sidecar).await {
+                let name = format!(
+                    "media/{}/sidecars/{}.xmp",
+                    media.uuid,
+                    FileService::clean_filename(filename)
+                );
+
+                files.push((name.clone(), sidecar.clone(), file.len()));
+                sidecars.push(name);
+            }
+        }
+
+        let modified = metadata
+            .as_ref()
+            .and_then(|m| m.taken_at)
+            .or_else(|| media.created_at.map(|c| c.naive_utc()))
+            .unwrap_or_else(|| Utc::now().naive_utc());
+
+        let item = ExportItemDto {
+            uuid: media.uuid,
+            media_type: media.media_type.clone(),
+            original,
+            original_filename,
+            sidecars,
+            favorite: media.favorite,
+            rating: media.rating,
+            archived: media.archived,
+            stack_uuid: media.stack_id.and_then(|id| stack_uuids.get(&id).copied()),
+            motion_media_uuid: media
+                .motion_media_id
+                .and_then(|id| media_uuids.get(&id).copied()),
+            tags: tags
+                .into_iter()
+                .map(|t| ExportTagDto {
+                    uuid: t.uuid,
+                    name: t.name,
+                })
+                .collect(),
+            metadata,
+            overrides,
+            history,
+            created_at: media.created_at,
+            updated_at: media.updated_at,
+        };
+
+        let json = serde_json::to_vec_pretty(&item)
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        Ok(ExportItem {
+            files,
+            json,
+            modified,
+        })
+    }
+
+    async fn open_part(directory: &str, part: u32) -> Result<PartWriter, AppError> {
+        let file = File::create(format!("{}.partial", Self::part_path(directory, part)))
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to create export".into()))?;
+
+        Ok(ArchiveWriter::new(BufWriter::new(file)))
+    }
+
+    /// Finishes a part and moves it into place under its final name.
+    async fn close_part(
+        directory: &str,
+        archive: PartWriter,
+        part: u32,
+        items: usize,
+    ) -> Result<ExportPartDto, AppError> {
+        let path = Self::part_path(directory, part);
+        let partial_path = format!("{}.partial", path);
+
+        archive
+            .finish()
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to write export".into()))?;
+
+        fs::rename(&partial_path, &path)
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to write export".into()))?;
+
+        let size = fs::metadata(&path)
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to write export".into()))?
+            .len();
+
+        Ok(ExportPartDto {
+            part,
+            filename: String::new(),
+            size,
+            items,
+        })
+    }
+
+    /// Only the newest export is kept on disk; earlier ones are a full copy of an
+    /// account that has since changed.
+    async fn remove_previous_exports(pool: &PgPool, user_id: i32, job_id: i32) {
+        let jobs = match JobService::list_jobs(pool, user_id).await {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                eprintln!("Failed to list previous exports: {}", e);
+                return;
+            }
+        };
+
+        for job in jobs.iter().filter(|j| {
+            j.id != job_id
+                && j.job_type == JobTypeEnum::ExportAccount
+                && j.status == JobStatusEnum::Completed
+        }) {
+            let directory = Self::export_dir(&job.uuid);
+
+            if Path::new(&directory).exists() {
+                if let Err(e) = fs::remove_dir_all(&directory).await {
+                    eprintln!("Failed to remove export {}: {}", job.id, e);
+                }
+            }
+        }
+    }
+
+    /// Path and download name of one part of a finished export job.
+    pub fn find_part(job: &JobModel, part: u32) -> Option<(String, String)> {
+        if job.job_type != JobTypeEnum::ExportAccount || job.status != JobStatusEnum::Completed {
+            return None;
+        }
+
+        let result: ExportResultDto = serde_json::from_value(job.result.clone()?).ok()?;
+        let found = result.parts.into_iter().find(|p| p.part == part)?;
+
+        Some((
+            Self::part_path(&Self::export_dir(&job.uuid), part),
+            found.filename,
+        ))
+    }
+
+    /// Exports are kept per job, e.g. `./exports/{job uuid}/part-1.zip`.
+    fn export_dir(job_uuid: &Uuid) -> String {
+        format!("{}/{}", EXPORT_DIR, job_uuid)
+    }
+
+    fn part_path(directory: &str, part: u32) -> String {
+        format!("{}/part-{}.zip", directory, part)
+    }
+}
+
+/// Files bound for the archive as (archive name, path on disk, size), plus the item's
+/// serialized `metadata.json`.
+struct ExportItem {
+    files: Vec<(String, String, u64)>,
+    json: Vec<u8>,
+    modified: NaiveDateTime,
+}
@@ -0,0 +1,3 @@
+pub mod export_service;
+
+pub use export_service::*;
@@ -0,0 +1,7 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct ImportFileResponseDto {
+    pub filename: String,
+    pub size: u64,
+}
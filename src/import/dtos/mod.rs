@@ -0,0 +1,11 @@
+pub mod import_file_response_dto;
+pub mod takeout_import_payload_dto;
+pub mod takeout_metadata_dto;
+pub mod watched_folder_payload_dto;
+pub mod watched_folder_update_payload_dto;
+
+pub use import_file_response_dto::*;
+pub use takeout_import_payload_dto::*;
+pub use takeout_metadata_dto::*;
+pub use watched_folder_payload_dto::*;
+pub use watched_folder_update_payload_dto::*;
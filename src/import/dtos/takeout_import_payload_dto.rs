@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// Archives in the user's import folder that make up one Takeout export. Google splits
+/// large exports over several ZIPs, and a file's `.json` can land in a different one.
+#[derive(Serialize, Deserialize)]
+pub struct TakeoutImportPayloadDto {
+    pub filenames: Vec<String>,
+}
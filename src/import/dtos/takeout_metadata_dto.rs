@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// The `.json` Google Takeout writes next to each file, and as `metadata.json` for each
+/// album. Unknown fields are ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TakeoutMetadataDto {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub photo_taken_time: Option<TakeoutTimestampDto>,
+    pub geo_data: Option<TakeoutGeoDataDto>,
+    pub geo_data_exif: Option<TakeoutGeoDataDto>,
+    #[serde(default)]
+    pub favorited: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub trashed: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TakeoutTimestampDto {
+    /// Seconds since the epoch, as a string.
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TakeoutGeoDataDto {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl TakeoutMetadataDto {
+    pub fn taken_at(&self) -> Option<DateTime<Utc>> {
+        let seconds = self.photo_taken_time.as_ref()?.timestamp.parse().ok()?;
+
+        DateTime::from_timestamp(seconds, 0)
+    }
+
+    /// Location set in Google Photos, else the one it read from the file. Google writes
+    /// 0, 0 when there is none.
+    pub fn location(&self) -> Option<(f64, f64)> {
+        [&self.geo_data, &self.geo_data_exif]
+            .into_iter()
+            .flatten()
+            .find(|geo| {
+                (geo.latitude != 0.0 || geo.longitude != 0.0)
+                    && (-90.0..=90.0).contains(&geo.latitude)
+                    && (-180.0..=180.0).contains(&geo.longitude)
+            })
+            .map(|geo| (geo.latitude, geo.longitude))
+    }
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::import::enums::watched_folder_mode_enum::WatchedFolderModeEnum;
+
+#[derive(Debug, Deserialize)]
+pub struct WatchedFolderPayloadDto {
+    pub user_uuid: Uuid,
+    pub path: String,
+    pub mode: Option<WatchedFolderModeEnum>,
+}
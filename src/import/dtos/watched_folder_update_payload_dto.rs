@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+use crate::import::enums::watched_folder_mode_enum::WatchedFolderModeEnum;
+
+#[derive(Debug, Deserialize)]
+pub struct WatchedFolderUpdatePayloadDto {
+    pub mode: Option<WatchedFolderModeEnum>,
+    pub enabled: Option<bool>,
+}
@@ -0,0 +1,2 @@
+pub mod watched_file_status_enum;
+pub mod watched_folder_mode_enum;
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "watched_file_status", rename_all = "lowercase")]
+pub enum WatchedFileStatusEnum {
+    Unknown = 0,
+    Imported = 1,
+    Duplicate = 2,
+    Skipped = 3,
+    Failed = 4,
+}
+
+impl From<i32> for WatchedFileStatusEnum {
+    fn from(status: i32) -> Self {
+        match status {
+            1 => WatchedFileStatusEnum::Imported,
+            2 => WatchedFileStatusEnum::Duplicate,
+            3 => WatchedFileStatusEnum::Skipped,
+            4 => WatchedFileStatusEnum::Failed,
+            _ => WatchedFileStatusEnum::Unknown,
+        }
+    }
+}
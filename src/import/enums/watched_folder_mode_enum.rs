@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+/// What happens to a file once it is imported: `Move` takes it into the user's upload
+/// directory, `Reference` leaves it where it is as part of an external library.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "watched_folder_mode", rename_all = "lowercase")]
+pub enum WatchedFolderModeEnum {
+    Move = 1,
+    Reference = 2,
+}
+
+impl From<i32> for WatchedFolderModeEnum {
+    fn from(mode: i32) -> Self {
+        match mode {
+            2 => WatchedFolderModeEnum::Reference,
+            _ => WatchedFolderModeEnum::Move,
+        }
+    }
+}
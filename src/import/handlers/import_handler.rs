@@ -0,0 +1,41 @@
+use axum::{
+    extract::{Multipart, State},
+    Extension, Json,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::errors::app_error::AppError;
+use crate::import::{
+    dtos::{ImportFileResponseDto, TakeoutImportPayloadDto},
+    services::{ImportService, TakeoutService},
+};
+use crate::job::models::JobModel;
+use crate::user::models::UserModel;
+
+pub async fn get_import_files(
+    Extension(user): Extension<UserModel>,
+) -> Result<Json<Vec<ImportFileResponseDto>>, AppError> {
+    let files = ImportService::list_files(&user).await?;
+
+    Ok(Json(files))
+}
+
+pub async fn upload_import_files(
+    Extension(user): Extension<UserModel>,
+    multipart: Multipart,
+) -> Result<Json<Vec<ImportFileResponseDto>>, AppError> {
+    let files = ImportService::save_files(&user, multipart).await?;
+
+    Ok(Json(files))
+}
+
+pub async fn import_takeout(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Json(payload): Json<TakeoutImportPayloadDto>,
+) -> Result<Json<JobModel>, AppError> {
+    let job = TakeoutService::request_import(&state.db, &user, payload).await?;
+
+    Ok(Json(job))
+}
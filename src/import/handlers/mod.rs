@@ -0,0 +1,5 @@
+pub mod import_handler;
+pub mod watched_folder_handler;
+
+pub use import_handler::*;
+pub use watched_folder_handler::*;
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::{fs, sync::Arc};
+
+use crate::app::AppState;
+use crate::errors::{app_error::AppError, database_error::is_unique_violation};
+use crate::import::{
+    dtos::{WatchedFolderPayloadDto, WatchedFolderUpdatePayloadDto},
+    enums::watched_folder_mode_enum::WatchedFolderModeEnum,
+    models::WatchedFolderModel,
+    services::WatchedFolderService,
+};
+use crate::user::services::UserService;
+
+pub async fn get_watched_folders(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<WatchedFolderModel>>, AppError> {
+    let folders = WatchedFolderService::list_folders(&state.db)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(folders))
+}
+
+pub async fn create_watched_folder(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<WatchedFolderPayloadDto>,
+) -> Result<Json<WatchedFolderModel>, AppError> {
+    let user = UserService::find_user_by_uuid(&state.db, payload.user_uuid)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    let path = validate_folder_path(&payload.path)?;
+    let mode = payload.mode.unwrap_or(WatchedFolderModeEnum::Move);
+
+    let folder = WatchedFolderService::create_folder(&state.db, user.id, &path, mode)
+        .await
+        .map_err(|e| match is_unique_violation(&e) {
+            true => AppError::BadRequest("Folder is already watched".into()),
+            false => AppError::InternalServerError("Something went wrong".into()),
+        })?;
+
+    state.import_watch.notify_one();
+
+    Ok(Json(folder))
+}
+
+pub async fn update_watched_folder(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Json(payload): Json<WatchedFolderUpdatePayloadDto>,
+) -> Result<Json<WatchedFolderModel>, AppError> {
+    let folder = WatchedFolderService::update_folder(&state.db, id, payload.mode, payload.enabled)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .ok_or_else(|| AppError::NotFound("Watched folder not found".into()))?;
+
+    state.import_watch.notify_one();
+
+    Ok(Json(folder))
+}
+
+pub async fn delete_watched_folder(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Result<Json<WatchedFolderModel>, AppError> {
+    let folder = WatchedFolderService::delete_folder(&state.db, id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .ok_or_else(|| AppError::NotFound("Watched folder not found".into()))?;
+
+    state.import_watch.notify_one();
+
+    Ok(Json(folder))
+}
+
+/// Folders are stored as absolute paths so watcher events can be matched to them.
+/// The upload directory is refused: watching it would import its renditions.
+fn validate_folder_path(path: &str) -> Result<String, AppError> {
+    let folder = fs::canonicalize(path.trim())
+        .ok()
+        .filter(|folder| folder.is_dir())
+        .ok_or_else(|| AppError::BadRequest("Folder not found".into()))?;
+
+    let uploads = fs::canonicalize("./uploads").ok();
+
+    if uploads.is_some_and(|uploads| folder.starts_with(&uploads) || uploads.starts_with(&folder)) {
+        return Err(AppError::BadRequest(
+            "Folder overlaps the upload directory".into(),
+        ));
+    }
+
+    folder
+        .to_str()
+        .map(str::to_string)
+        .ok_or_else(|| AppError::BadRequest("Folder path must be valid UTF-8".into()))
+}
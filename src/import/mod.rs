@@ -0,0 +1,6 @@
+pub mod dtos;
+pub mod enums;
+pub mod handlers;
+pub mod models;
+pub mod routes;
+pub mod services;
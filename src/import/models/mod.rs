@@ -0,0 +1,5 @@
+pub mod watched_file_model;
+pub mod watched_folder_model;
+
+pub use watched_file_model::*;
+pub use watched_folder_model::*;
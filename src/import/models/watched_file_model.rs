@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::import::enums::watched_file_status_enum::WatchedFileStatusEnum;
+
+/// A file seen in a watched folder, keyed by its path relative to the folder. Size and
+/// modification time tell a rescan whether the file has changed since.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct WatchedFileModel {
+    pub id: i32,
+    pub uuid: Uuid,
+
+    pub watched_folder_id: i32,
+    pub path: String,
+    pub size: i64,
+    pub modified_at: DateTime<Utc>,
+    pub status: WatchedFileStatusEnum,
+    pub media_id: Option<i32>,
+    pub error: Option<String>,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::import::enums::watched_folder_mode_enum::WatchedFolderModeEnum;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WatchedFolderModel {
+    pub id: i32,
+    pub uuid: Uuid,
+
+    pub user_id: i32,
+    pub path: String,
+    pub mode: WatchedFolderModeEnum,
+    pub enabled: bool,
+    pub last_scanned_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
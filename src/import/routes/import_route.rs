@@ -0,0 +1,42 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, patch, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::auth::middlewares::{admin_middleware, auth_middleware};
+use crate::import::handlers::{
+    create_watched_folder, delete_watched_folder, get_import_files, get_watched_folders,
+    import_takeout, update_watched_folder, upload_import_files,
+};
+
+pub fn import_routes(app_state: Arc<AppState>) -> Router {
+    let admin_routes = Router::new()
+        .route(
+            "/admin/watched-folders",
+            get(get_watched_folders).post(create_watched_folder),
+        )
+        .route(
+            "/admin/watched-folders/{id}",
+            patch(update_watched_folder).delete(delete_watched_folder),
+        )
+        .layer(middleware::from_fn(admin_middleware));
+
+    Router::new()
+        .route(
+            "/imports/files",
+            get(get_import_files)
+                .post(upload_import_files)
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route("/imports/takeout", post(import_takeout))
+        .merge(admin_routes)
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state)
+}
@@ -0,0 +1,3 @@
+pub mod import_route;
+
+pub use import_route::*;
@@ -0,0 +1,139 @@
+use axum::extract::Multipart;
+use std::path::Path;
+use tokio::{
+    fs::{self, File},
+    io::AsyncWriteExt,
+};
+
+use crate::config::get_import_dir;
+use crate::errors::app_error::AppError;
+use crate::import::dtos::ImportFileResponseDto;
+use crate::media::services::FileService;
+use crate::user::models::UserModel;
+
+pub struct ImportService {}
+
+impl ImportService {
+    /// Each user's drop folder for archives to import, e.g. `./imports/{user uuid}`.
+    pub fn user_dir(user: &UserModel) -> String {
+        format!("{}/{}", get_import_dir(), user.uuid)
+    }
+
+    /// Path of a file directly inside the user's import folder. Anything that is not a
+    /// bare filename is refused, so a request can never reach outside it.
+    pub fn resolve_file(user: &UserModel, filename: &str) -> Option<String> {
+        if filename.is_empty()
+            || filename == "."
+            || filename == ".."
+            || filename.contains(['/', '\\', '\0'])
+        {
+            return None;
+        }
+
+        let path = format!("{}/{}", Self::user_dir(user), filename);
+
+        Path::new(&path).is_file().then_some(path)
+    }
+
+    pub async fn list_files(user: &UserModel) -> Result<Vec<ImportFileResponseDto>, AppError> {
+        let mut entries = match fs::read_dir(Self::user_dir(user)).await {
+            Ok(entries) => entries,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut files = Vec::new();
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        {
+            let (Ok(metadata), Ok(filename)) =
+                (entry.metadata().await, entry.file_name().into_string())
+            else {
+                continue;
+            };
+
+            if metadata.is_file() && !filename.ends_with(".partial") {
+                files.push(ImportFileResponseDto {
+                    filename,
+                    size: metadata.len(),
+                });
+            }
+        }
+
+        files.sort_by(|a, b| a.filename.cmp(&b.filename));
+
+        Ok(files)
+    }
+
+    /// Streams every `file` field of the request into the user's import folder. Takeout
+    /// archives run to tens of gigabytes, so nothing is held in memory.
+    pub async fn save_files(
+        user: &UserModel,
+        mut multipart: Multipart,
+    ) -> Result<Vec<ImportFileResponseDto>, AppError> {
+        let directory = Self::user_dir(user);
+
+        fs::create_dir_all(&directory)
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to create import dir".into()))?;
+
+        let mut saved = Vec::new();
+
+        while let Some(mut field) = multipart
+            .next_field()
+            .await
+            .map_err(|_| AppError::BadRequest("Something went wrong".into()))?
+        {
+            if field.name() != Some("file") {
+                continue;
+            }
+
+            let filename = FileService::clean_filename(field.file_name().unwrap_or_default());
+
+            if !filename.to_lowercase().ends_with(".zip") {
+                return Err(AppError::BadRequest(
+                    "Only ZIP archives can be imported".into(),
+                ));
+            }
+
+            let path = format!("{}/{}", directory, filename);
+            let partial_path = format!("{}.partial", path);
+
+            let mut file = File::create(&partial_path)
+                .await
+                .map_err(|_| AppError::InternalServerError("Failed to create file".into()))?;
+            let mut size = 0u64;
+
+            while let Some(chunk) = match field.chunk().await {
+                Ok(chunk) => chunk,
+                Err(_) => {
+                    let _ = fs::remove_file(&partial_path).await;
+                    return Err(AppError::BadRequest("Upload was interrupted".into()));
+                }
+            } {
+                file.write_all(&chunk)
+                    .await
+                    .map_err(|_| AppError::InternalServerError("Failed to write file".into()))?;
+                size += chunk.len() as u64;
+            }
+
+            file.flush()
+                .await
+                .map_err(|_| AppError::InternalServerError("Failed to write file".into()))?;
+
+            fs::rename(&partial_path, &path)
+                .await
+                .map_err(|_| AppError::InternalServerError("Failed to write file".into()))?;
+
+            saved.push(ImportFileResponseDto { filename, size });
+        }
+
+        if saved.is_empty() {
+            return Err(AppError::BadRequest("Missing file".into()));
+        }
+
+        Ok(saved)
+    }
+}
@@ -0,0 +1,9 @@
+pub mod import_service;
+pub mod takeout_service;
+pub mod watch_service;
+pub mod watched_folder_service;
+
+pub use import_service::*;
+pub use takeout_service::*;
+pub use watch_service::*;
+pub use watched_folder_service::*;
@@ -0,0 +1,640 @@
+use chrono::{DateTime, FixedOffset};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+};
+use tokio::sync::mpsc;
+use zip::ZipArchive;
+
+use crate::errors::app_error::AppError;
+use crate::import::{
+    dtos::{TakeoutImportPayloadDto, TakeoutMetadataDto},
+    services::ImportService,
+};
+use crate::job::{enums::job_type_enum::JobTypeEnum, models::JobModel, services::JobService};
+use crate::media::{
+    dtos::{MediaBulkUpdatePayloadDto, MediaMetadataUpdatePayloadDto},
+    enums::media_type_enum::MediaTypeEnum,
+    models::{MediaMetadataModel, MediaModel},
+    services::{
+        FileService, MediaMetadataOverrideService, MediaMetadataService, MediaService,
+        UploadService,
+    },
+};
+use crate::tag::services::TagService;
+use crate::user::{models::UserModel, services::UserService};
+
+/// Newer exports name sidecars `IMG_1234.JPG.supplemental-metadata.json`, often cut
+/// short to fit the name limit below.
+const SIDECAR_SUFFIX: &str = ".supplemental-metadata";
+/// Sidecar names this long, `.json` included, may have had the media name cut short.
+const SIDECAR_TRUNCATED_LENGTH: usize = 46;
+const SIDECAR_MAX_BYTES: u64 = 1024 * 1024;
+/// Each album folder describes itself in a file of this name; year folders have none.
+const ALBUM_METADATA: &str = "metadata.json";
+const EDITED_SUFFIX: &str = "-edited";
+/// How far Google's taken time may drift from the file's before it counts as an edit.
+const TAKEN_AT_TOLERANCE_SECONDS: i64 = 60;
+/// Files extracted ahead of the import, bounding the disk used by files in flight.
+const EXTRACT_AHEAD: usize = 4;
+
+/// A media file in one of the archives with what Takeout recorded about it.
+struct TakeoutEntry {
+    archive: usize,
+    index: usize,
+    name: String,
+    metadata: Option<TakeoutMetadataDto>,
+    album: Option<String>,
+}
+
+/// An entry written to the user's upload directory as (file name, path, hash).
+struct TakeoutExtracted {
+    entry: TakeoutEntry,
+    file: Result<(String, String, String), String>,
+}
+
+enum TakeoutOutcome {
+    Imported,
+    Duplicate,
+    Skipped,
+}
+
+/// Sidecars of one folder keyed by the media name and duplicate counter they describe,
+/// with whether the name may have been cut short.
+type FolderSidecars = HashMap<(String, u32), (TakeoutMetadataDto, bool)>;
+
+pub struct TakeoutService {}
+
+impl TakeoutService {
+    /// Queues an import of Takeout archives from the user's import folder, unless the
+    /// same archives are already being imported.
+    pub async fn request_import(
+        pool: &PgPool,
+        user: &UserModel,
+        payload: TakeoutImportPayloadDto,
+    ) -> Result<JobModel, AppError> {
+        if payload.filenames.is_empty() {
+            return Err(AppError::BadRequest("Select at least one archive".into()));
+        }
+
+        let archives = payload
+            .filenames
+            .iter()
+            .map(|filename| {
+                ImportService::resolve_file(user, filename)
+                    .ok_or_else(|| AppError::NotFound(format!("{} not found", filename)))
+            })
+            .collect::<Result<Vec<String>, AppError>>()?;
+
+        let payload = serde_json::to_value(&payload)
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if let Some(job) =
+            JobService::find_active_job(pool, user.id, JobTypeEnum::ImportTakeout, &payload)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        {
+            return Ok(job);
+        }
+
+        let job = JobService::create_job(pool, user, JobTypeEnum::ImportTakeout, Some(payload))
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let task_pool = pool.clone();
+        let task_user = user.clone();
+        let job_id = job.id;
+
+        JobService::spawn(pool.clone(), user.clone(), &job, async move {
+            Self::import_takeout(&task_pool, job_id, &task_user, archives).await
+        });
+
+        Ok(job)
+    }
+
+    /// Indexes every archive first, so sidecars pair up across parts, then extracts
+    /// media on a blocking thread while this task feeds it through the upload pipeline.
+    async fn import_takeout(
+        pool: &PgPool,
+        job_id: i32,
+        user: &UserModel,
+        archives: Vec<String>,
+    ) -> Result<Option<serde_json::Value>, AppError> {
+        let paths = archives.clone();
+
+        let entries = tokio::task::spawn_blocking(move || Self::scan(&paths))
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+            .map_err(|e| AppError::BadRequest(format!("Failed to read archive: {}", e)))?;
+
+        JobService::mark_running(pool, job_id, entries.len() as i32)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        UserService::create_user_directory(user).await?;
+
+        let upload_dir = format!("./uploads/{}", user.uuid);
+        let (sender, mut receiver) = mpsc::channel(EXTRACT_AHEAD);
+
+        let extractor = tokio::task::spawn_blocking(move || {
+            Self::extract(&archives, entries, &upload_dir, sender)
+        });
+
+        let mut album_tags = HashMap::new();
+        let (mut imported, mut duplicates, mut skipped, mut failed) = (0, 0, 0, 0);
+        let mut processed = 0;
+
+        while let Some(extracted) = receiver.recv().await {
+            let name = extracted.entry.name.clone();
+
+            match Self::import_entry(pool, user, extracted, &mut album_tags).await {
+                Ok(TakeoutOutcome::Imported) => imported += 1,
+                Ok(TakeoutOutcome::Duplicate) => duplicates += 1,
+                Ok(TakeoutOutcome::Skipped) => skipped += 1,
+                Err(e) => {
+                    eprintln!("Failed to import {} from Takeout: {:?}", name, e);
+                    failed += 1;
+                }
+            }
+
+            processed += 1;
+            let _ = JobService::update_progress(pool, job_id, processed).await;
+        }
+
+        extractor
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+            .map_err(|e| AppError::BadRequest(format!("Failed to read archive: {}", e)))?;
+
+        Ok(Some(json!({
+            "imported": imported,
+            "duplicates": duplicates,
+            "skipped": skipped,
+            "failed": failed,
+            "albums": album_tags.len(),
+        })))
+    }
+
+    async fn import_entry(
+        pool: &PgPool,
+        user: &UserModel,
+        extracted: TakeoutExtracted,
+        album_tags: &mut HashMap<String, i32>,
+    ) -> Result<TakeoutOutcome, AppError> {
+        let TakeoutExtracted { entry, file } = extracted;
+        let (file_name, path, hash) = file.map_err(AppError::InternalServerError)?;
+
+        // Takeout repeats a photo in its year folder and every album holding it, so a
+        // known file only gains album membership.
+        let existing = MediaService::find_media_by_hash(pool, user.id, &hash)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if let Some(media) = existing {
+            let _ = fs::remove_file(&path);
+            Self::add_to_album(pool, user, media.id, entry.album.as_deref(), album_tags).await?;
+
+            return Ok(TakeoutOutcome::Duplicate);
+        }
+
+        let mime_type = MediaMetadataService::detect_mime_type(&path, &entry.name)?;
+
+        if MediaTypeEnum::from_mime(&mime_type) == MediaTypeEnum::Unknown {
+            let _ = fs::remove_file(&path);
+
+            return Ok(TakeoutOutcome::Skipped);
+        }
+
+        let media = UploadService::ingest_file(pool, user, &file_name, &path, &entry.name).await?;
+
+        if let Some(metadata) = &entry.metadata {
+            Self::apply_metadata(pool, user, &media, metadata).await?;
+        }
+
+        Self::add_to_album(pool, user, media.id, entry.album.as_deref(), album_tags).await?;
+
+        Ok(TakeoutOutcome::Imported)
+    }
+
+    /// Google keeps captions, dates and places edited in Google Photos only in the
+    /// sidecar, so they become overrides on top of what was read from the file.
+    async fn apply_metadata(
+        pool: &PgPool,
+        user: &UserModel,
+        media: &MediaModel,
+        metadata: &TakeoutMetadataDto,
+    ) -> Result<(), AppError> {
+        let extracted = MediaMetadataService::get_metadata_for_media(pool, media.id)
+            .await
+            .ok();
+
+        let description = metadata
+            .description
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(|d| Some(d.to_string()));
+        let taken_at = Self::taken_at(metadata, extracted.as_ref()).map(Some);
+        let location = metadata.location();
+
+        if description.is_some() || taken_at.is_some() || location.is_some() {
+            let payload = MediaMetadataUpdatePayloadDto {
+                title: None,
+                description,
+                taken_at,
+                latitude: location.map(|(latitude, _)| Some(latitude)),
+                longitude: location.map(|(_, longitude)| Some(longitude)),
+                location_name: None,
+            };
+
+            MediaMetadataOverrideService::update_overrides(pool, media.id, payload)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        if metadata.favorited || metadata.archived {
+            let payload = MediaBulkUpdatePayloadDto {
+                media_ids: vec![media.id],
+                favorite: metadata.favorited.then_some(true),
+                rating: None,
+                archived: metadata.archived.then_some(true),
+            };
+
+            MediaService::bulk_update_media(pool, user.id, &payload)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Google's taken time when the file has none, or when it differs from the file's
+    /// because it was changed in Google Photos. A file time without an offset cannot be
+    /// compared with Google's UTC time, so it is kept.
+    fn taken_at(
+        metadata: &TakeoutMetadataDto,
+        extracted: Option<&MediaMetadataModel>,
+    ) -> Option<DateTime<FixedOffset>> {
+        let taken_at = metadata.taken_at()?;
+
+        let Some(local) = extracted.and_then(|m| m.taken_at) else {
+            return Some(taken_at.fixed_offset());
+        };
+
+        let offset: FixedOffset = extracted?.taken_at_offset.as_deref()?.parse().ok()?;
+        let file_taken_at = local.and_local_timezone(offset).single()?;
+
+        ((file_taken_at.timestamp() - taken_at.timestamp()).abs() > TAKEN_AT_TOLERANCE_SECONDS)
+            .then(|| taken_at.with_timezone(&offset))
+    }
+
+    /// Albums become tags of the same name, created once per import.
+    async fn add_to_album(
+        pool: &PgPool,
+        user: &UserModel,
+        media_id: i32,
+        album: Option<&str>,
+        album_tags: &mut HashMap<String, i32>,
+    ) -> Result<(), AppError> {
+        let Some(album) = album else {
+            return Ok(());
+        };
+
+        let tag_id = match album_tags.get(album) {
+            Some(tag_id) => *tag_id,
+            None => {
+                let tag = TagService::find_or_create_tag(pool, user.id, album)
+                    .await
+                    .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+                album_tags.insert(album.to_string(), tag.id);
+                tag.id
+            }
+        };
+
+        TagService::attach_media(pool, tag_id, user.id, &[media_id])
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        Ok(())
+    }
+
+    /// Lists the media files across all archives, each paired with its sidecar and
+    /// album. Trashed items are left out.
+    fn scan(
+        archives: &[String],
+    ) -> Result<Vec<TakeoutEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut sidecars: HashMap<String, FolderSidecars> = HashMap::new();
+        let mut albums: HashMap<String, String> = HashMap::new();
+        let mut media = Vec::new();
+
+        for (archive_index, path) in archives.iter().enumerate() {
+            let mut archive = ZipArchive::new(File::open(path)?)?;
+
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index)?;
+
+                if file.is_dir() {
+                    continue;
+                }
+
+                // Entry names are untrusted; anything escaping the archive root is skipped.
+                let Some(entry_path) = file.enclosed_name() else {
+                    continue;
+                };
+                let folder = entry_path
+                    .parent()
+                    .and_then(|p| p.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                let Some(name) = entry_path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                let name = name.to_string();
+                let lower = name.to_lowercase();
+
+                if lower.ends_with(".html") {
+                    continue;
+                }
+
+                if !lower.ends_with(".json") {
+                    media.push((archive_index, index, folder, name));
+                    continue;
+                }
+
+                if file.size() > SIDECAR_MAX_BYTES {
+                    continue;
+                }
+
+                let mut data = Vec::new();
+                file.read_to_end(&mut data)?;
+
+                let Ok(metadata) = serde_json::from_slice::<TakeoutMetadataDto>(&data) else {
+                    continue;
+                };
+
+                if name == ALBUM_METADATA {
+                    if let Some(title) = metadata.title.as_deref().map(str::trim) {
+                        if !title.is_empty() {
+                            albums.insert(folder, title.to_string());
+                        }
+                    }
+
+                    continue;
+                }
+
+                let (base, counter) = Self::sidecar_key(&name);
+                let truncated = name.chars().count() >= SIDECAR_TRUNCATED_LENGTH;
+
+                sidecars
+                    .entry(folder)
+                    .or_default()
+                    .insert((base, counter), (metadata, truncated));
+            }
+        }
+
+        let entries = media
+            .into_iter()
+            .filter_map(|(archive, index, folder, name)| {
+                let metadata = sidecars
+                    .get(&folder)
+                    .and_then(|s| Self::find_sidecar(s, &name))
+                    .cloned();
+
+                if metadata.as_ref().is_some_and(|m| m.trashed) {
+                    return None;
+                }
+
+                Some(TakeoutEntry {
+                    archive,
+                    index,
+                    album: albums.get(&folder).cloned(),
+                    name,
+                    metadata,
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Writes each entry into the upload directory and hands it over, stopping early if
+    /// the import has gone away.
+    fn extract(
+        archives: &[String],
+        entries: Vec<TakeoutEntry>,
+        upload_dir: &str,
+        sender: mpsc::Sender<TakeoutExtracted>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut opened = archives
+            .iter()
+            .map(|path| Ok(ZipArchive::new(File::open(path)?)?))
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error + Send + Sync>>>()?;
+
+        for entry in entries {
+            let file = Self::extract_entry(&mut opened[entry.archive], &entry, upload_dir)
+                .map_err(|e| e.to_string());
+
+            if sender
+                .blocking_send(TakeoutExtracted { entry, file })
+                .is_err()
+            {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn extract_entry(
+        archive: &mut ZipArchive<File>,
+        entry: &TakeoutEntry,
+        upload_dir: &str,
+    ) -> Result<(String, String, String), Box<dyn std::error::Error>> {
+        let file_name = FileService::sanitize_filename(&entry.name);
+        let path = format!("{}/{}", upload_dir, file_name);
+
+        let written = archive
+            .by_index(entry.index)
+            .map_err(io::Error::from)
+            .and_then(|mut source| io::copy(&mut source, &mut File::create(&path)?));
+
+        if let Err(e) = written {
+            let _ = fs::remove_file(&path);
+            return Err(e.into());
+        }
+
+        let hash = FileService::generate_file_hash(&path).map_err(|e| format!("{:?}", e))?;
+
+        Ok((file_name, path, hash))
+    }
+
+    /// Looks a media file's sidecar up the ways Takeout names them: `IMG.jpg.json`,
+    /// `IMG.jpg(1).json` for `IMG(1).jpg`, the original's for `IMG-edited.jpg`, a name
+    /// cut short, and finally the photo's sidecar for the video half of a Live Photo.
+    fn find_sidecar<'a>(
+        sidecars: &'a FolderSidecars,
+        name: &str,
+    ) -> Option<&'a TakeoutMetadataDto> {
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (name, String::new()),
+        };
+        let (unnumbered, counter) = Self::split_counter(stem);
+
+        let mut candidates = vec![
+            (name.to_string(), 0),
+            (format!("{}{}", unnumbered, extension), counter),
+        ];
+
+        if let Some(original) = stem.strip_suffix(EDITED_SUFFIX) {
+            candidates.push((format!("{}{}", original, extension), 0));
+        }
+
+        if let Some((metadata, _)) = candidates.iter().find_map(|key| sidecars.get(key)) {
+            return Some(metadata);
+        }
+
+        let full_name = format!("{}{}", unnumbered, extension);
+
+        let truncated = sidecars
+            .iter()
+            .filter(|((base, c), (_, truncated))| {
+                *truncated && *c == counter && full_name.starts_with(base.as_str())
+            })
+            .max_by_key(|((base, _), _)| base.len());
+
+        if let Some((_, (metadata, _))) = truncated {
+            return Some(metadata);
+        }
+
+        sidecars
+            .iter()
+            .find(|((base, c), _)| {
+                *c == counter
+                    && base
+                        .rsplit_once('.')
+                        .is_some_and(|(base_stem, _)| base_stem == unnumbered)
+            })
+            .map(|(_, (metadata, _))| metadata)
+    }
+
+    /// The media name and duplicate counter a sidecar describes:
+    /// `IMG.jpg.supplemental-meta(1).json` is `IMG.jpg` with counter 1.
+    fn sidecar_key(name: &str) -> (String, u32) {
+        let stem = &name[..name.len() - ".json".len()];
+        let (stem, counter) = Self::split_counter(stem);
+
+        let base = match stem.rfind('.') {
+            Some(index) if index > 0 && SIDECAR_SUFFIX.starts_with(&stem[index..]) => {
+                &stem[..index]
+            }
+            _ => stem,
+        };
+
+        (base.to_string(), counter)
+    }
+
+    /// `IMG(2)` is `IMG` with counter 2; a name without one has counter 0.
+    fn split_counter(stem: &str) -> (&str, u32) {
+        stem.strip_suffix(')')
+            .and_then(|s| s.rsplit_once('('))
+            .and_then(|(base, digits)| Some((base, digits.parse().ok()?)))
+            .filter(|(base, _)| !base.is_empty())
+            .unwrap_or((stem, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sidecars as `scan` collects them for one folder, each titled with its own name
+    /// so a lookup shows which one it found.
+    fn folder(names: &[&str]) -> FolderSidecars {
+        names
+            .iter()
+            .map(|name| {
+                let metadata = TakeoutMetadataDto {
+                    title: Some(name.to_string()),
+                    ..Default::default()
+                };
+                let truncated = name.chars().count() >= SIDECAR_TRUNCATED_LENGTH;
+
+                (TakeoutService::sidecar_key(name), (metadata, truncated))
+            })
+            .collect()
+    }
+
+    fn found<'a>(sidecars: &'a FolderSidecars, name: &str) -> Option<&'a str> {
+        TakeoutService::find_sidecar(sidecars, name).and_then(|m| m.title.as_deref())
+    }
+
+    #[test]
+    fn sidecar_key_strips_suffixes_and_counters() {
+        let cases = [
+            ("IMG_0001.jpg.json", ("IMG_0001.jpg", 0)),
+            (
+                "IMG_0001.jpg.supplemental-metadata.json",
+                ("IMG_0001.jpg", 0),
+            ),
+            ("IMG_0001.jpg.supplemental-me.json", ("IMG_0001.jpg", 0)),
+            (
+                "IMG_0001.jpg.supplemental-metadata(1).json",
+                ("IMG_0001.jpg", 1),
+            ),
+            ("IMG_0001.jpg(2).json", ("IMG_0001.jpg", 2)),
+            ("(1).json", ("(1)", 0)),
+        ];
+
+        for (name, (base, counter)) in cases {
+            assert_eq!(
+                TakeoutService::sidecar_key(name),
+                (base.to_string(), counter),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn numbered_media_find_their_numbered_sidecar() {
+        let sidecars = folder(&["IMG_0001.jpg.json", "IMG_0001.jpg(1).json"]);
+
+        assert_eq!(found(&sidecars, "IMG_0001.jpg"), Some("IMG_0001.jpg.json"));
+        assert_eq!(
+            found(&sidecars, "IMG_0001(1).jpg"),
+            Some("IMG_0001.jpg(1).json")
+        );
+        assert_eq!(found(&sidecars, "IMG_0001(2).jpg"), None);
+    }
+
+    #[test]
+    fn truncated_sidecars_match_by_prefix() {
+        let media = "Screenshot_20230815-123456_Messenger-Lite-App.png";
+        let sidecar = format!("{}.json", &media[..41]);
+        let shorter = format!("{}.json", &media[..30]);
+        let sidecars = folder(&[&sidecar, "Screenshot_2023.json", &shorter]);
+
+        assert_eq!(sidecar.chars().count(), SIDECAR_TRUNCATED_LENGTH);
+        assert_eq!(found(&sidecars, media), Some(sidecar.as_str()));
+    }
+
+    #[test]
+    fn edited_copies_and_live_photo_videos_share_a_sidecar() {
+        let sidecars = folder(&["IMG_0002.HEIC.supplemental-metadata.json"]);
+
+        assert_eq!(
+            found(&sidecars, "IMG_0002-edited.HEIC"),
+            Some("IMG_0002.HEIC.supplemental-metadata.json")
+        );
+        assert_eq!(
+            found(&sidecars, "IMG_0002.MP4"),
+            Some("IMG_0002.HEIC.supplemental-metadata.json")
+        );
+        assert_eq!(found(&sidecars, "IMG_0003.MP4"), None);
+    }
+}
@@ -0,0 +1,593 @@
+use chrono::{DateTime, SubsecRound, Utc};
+use notify::{
+    event::{ModifyKind, RenameMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use sqlx::PgPool;
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, Notify},
+    time::{self, Instant, MissedTickBehavior},
+};
+use tracing::{error, info, warn};
+
+use crate::auth::services::AuthService;
+use crate::config::{get_watch_rescan_seconds, get_watch_settle_seconds};
+use crate::errors::app_error::AppError;
+use crate::import::{
+    enums::{
+        watched_file_status_enum::WatchedFileStatusEnum,
+        watched_folder_mode_enum::WatchedFolderModeEnum,
+    },
+    models::{WatchedFileModel, WatchedFolderModel},
+    services::WatchedFolderService,
+};
+use crate::media::{
+    enums::media_type_enum::MediaTypeEnum,
+    models::MediaModel,
+    services::{
+        FileService, HlsService, MediaMetadataService, MediaService, ReextractService,
+        ThumbnailService, UploadService, XmpService, HLS_DIR, MOTION_DIR, PLAYBACK_DIR, POSTER_DIR,
+        RENDITION_DIR, SPRITE_DIR,
+    },
+};
+use crate::user::{models::UserModel, services::UserService};
+
+/// A file found in a watched folder. `path` is relative to the folder and is what the
+/// folder's file records are keyed by.
+pub struct WatchedFileEntry {
+    pub path: String,
+    pub absolute: PathBuf,
+    pub size: i64,
+    pub modified_at: DateTime<Utc>,
+}
+
+pub struct WatchService {}
+
+impl WatchService {
+    /// Watches every enabled folder for as long as the server runs. A change seen by
+    /// inotify schedules a scan of its folder once the folder has been quiet for the
+    /// settle time, so files still being copied in are left alone. The periodic rescan
+    /// catches what inotify cannot see: network mounts, overflowed event queues and
+    /// anything that arrived while the server was down. `reload` is notified whenever
+    /// an admin changes the folders.
+    pub async fn run(pool: PgPool, reload: Arc<Notify>) {
+        let settle = Duration::from_secs(get_watch_settle_seconds());
+        let (sender, mut receiver) = mpsc::unbounded_channel::<PathBuf>();
+
+        let mut rescan = time::interval(Duration::from_secs(get_watch_rescan_seconds()));
+        rescan.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let (mut folders, mut _watcher) = Self::load(&pool, &sender).await;
+        let mut due: HashMap<i32, Instant> = HashMap::new();
+
+        loop {
+            let next = due.values().min().copied();
+
+            tokio::select! {
+                _ = rescan.tick() => {
+                    for folder in &folders {
+                        due.entry(folder.id).or_insert_with(Instant::now);
+                    }
+                }
+                _ = reload.notified() => {
+                    (folders, _watcher) = Self::load(&pool, &sender).await;
+                    due.clear();
+
+                    for folder in &folders {
+                        due.insert(folder.id, Instant::now());
+                    }
+                }
+                Some(path) = receiver.recv() => {
+                    if let Some(folder) = folders.iter().find(|f| path.starts_with(&f.path)) {
+                        due.insert(folder.id, Instant::now() + settle);
+                    }
+                }
+                _ = time::sleep_until(next.unwrap_or_else(Instant::now)), if next.is_some() => {
+                    let now = Instant::now();
+
+                    for folder in &folders {
+                        if due.get(&folder.id).is_none_or(|at| *at > now) {
+                            continue;
+                        }
+
+                        due.remove(&folder.id);
+
+                        if Self::scan_folder(&pool, folder, settle).await {
+                            due.entry(folder.id).or_insert(Instant::now() + settle);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Enabled folders and a watcher over them. Folders inotify cannot watch are still
+    /// picked up by rescans.
+    async fn load(
+        pool: &PgPool,
+        sender: &mpsc::UnboundedSender<PathBuf>,
+    ) -> (Vec<WatchedFolderModel>, Option<RecommendedWatcher>) {
+        let folders = WatchedFolderService::list_enabled_folders(pool)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to load watched folders: {}", e);
+                Vec::new()
+            });
+
+        let sender = sender.clone();
+
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+
+            if Self::is_arrival(&event.kind) {
+                for path in event.paths {
+                    let _ = sender.send(path);
+                }
+            }
+        });
+
+        let mut watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                warn!("File watching unavailable, relying on rescans: {}", e);
+                return (folders, None);
+            }
+        };
+
+        for folder in &folders {
+            if let Err(e) = watcher.watch(Path::new(&folder.path), RecursiveMode::Recursive) {
+                warn!("Cannot watch {}, relying on rescans: {}", folder.path, e);
+            }
+        }
+
+        (folders, Some(watcher))
+    }
+
+    /// Files being written or moved in. Files moved out, including by the import
+    /// itself, are not worth a scan.
+    fn is_arrival(kind: &EventKind) -> bool {
+        match kind {
+            EventKind::Create(_) => true,
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => false,
+            EventKind::Modify(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Imports whatever is new or changed in the folder on behalf of its owner.
+    /// Returns whether files were left for later because they are still being written.
+    async fn scan_folder(pool: &PgPool, folder: &WatchedFolderModel, settle: Duration) -> bool {
+        let user = match UserService::find_user_by_id(pool, folder.user_id).await {
+            Ok(Some(user)) => user,
+            _ => return false,
+        };
+
+        let result =
+            AuthService::login(user.clone(), Self::scan(pool, &user, folder, settle)).await;
+
+        let (unsettled, error) = match result {
+            Ok(unsettled) => (unsettled, None),
+            Err(e) => {
+                warn!("Failed to scan watched folder {}: {}", folder.path, e);
+                (false, Some(e))
+            }
+        };
+
+        if let Err(e) = WatchedFolderService::mark_scanned(pool, folder.id, error.as_deref()).await
+        {
+            error!("Failed to update watched folder {}: {}", folder.id, e);
+        }
+
+        unsettled
+    }
+
+    async fn scan(
+        pool: &PgPool,
+        user: &UserModel,
+        folder: &WatchedFolderModel,
+        settle: Duration,
+    ) -> Result<bool, String> {
+        let root = PathBuf::from(&folder.path);
+
+        let mut files = tokio::task::spawn_blocking(move || Self::walk(&root))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Failed to read folder: {}", e))?;
+
+        let known: HashMap<String, WatchedFileModel> =
+            WatchedFolderService::list_files(pool, folder.id)
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|file| (file.path.clone(), file))
+                .collect();
+
+        // Sidecars go last so the media they describe is already there.
+        files.sort_by_key(|file| XmpService::is_sidecar(&file.path));
+
+        let cutoff = Utc::now() - settle;
+        let mut unsettled = false;
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut present = HashSet::new();
+
+        for file in files {
+            present.insert(file.path.clone());
+            let record = known.get(&file.path);
+
+            if record.is_some_and(|k| k.size == file.size && k.modified_at == file.modified_at) {
+                continue;
+            }
+
+            if file.modified_at > cutoff {
+                unsettled = true;
+                continue;
+            }
+
+            let referencing = match record {
+                Some(record) => Self::find_referencing_media(pool, record, &file.absolute).await,
+                None => None,
+            };
+
+            let result = match referencing {
+                Some(media) => Self::refresh_media(pool, user, &media).await,
+                None => Self::import_file(pool, user, folder, &file).await,
+            };
+
+            let (status, media_id, error) = match result {
+                Ok((status, media_id)) => (status, media_id, None),
+                Err(e) => {
+                    warn!("Failed to import {}: {:?}", file.absolute.display(), e);
+                    (
+                        WatchedFileStatusEnum::Failed,
+                        None,
+                        Some(format!("{:?}", e)),
+                    )
+                }
+            };
+
+            *counts.entry(Self::status_label(&status)).or_default() += 1;
+
+            WatchedFolderService::record_file(
+                pool,
+                folder.id,
+                &file,
+                status,
+                media_id,
+                error.as_deref(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+
+        for record in known.values().filter(|k| !present.contains(&k.path)) {
+            let source = Path::new(&folder.path).join(&record.path);
+
+            if Self::retire_file(pool, record, &source).await? {
+                *counts.entry("removed").or_default() += 1;
+            }
+        }
+
+        if !counts.is_empty() {
+            info!("Scanned watched folder {}: {:?}", folder.path, counts);
+        }
+
+        Ok(unsettled)
+    }
+
+    /// The media a record was imported as, while it is still a link to `source`: a
+    /// referenced original rather than a moved-in copy or a duplicate.
+    async fn find_referencing_media(
+        pool: &PgPool,
+        record: &WatchedFileModel,
+        source: &Path,
+    ) -> Option<MediaModel> {
+        if record.status != WatchedFileStatusEnum::Imported || XmpService::is_sidecar(&record.path)
+        {
+            return None;
+        }
+
+        let media = MediaService::media_detail(pool, record.media_id?)
+            .await
+            .ok()?;
+
+        let target = fs::read_link(&media.filepath).ok()?;
+
+        (target == source).then_some(media)
+    }
+
+    /// Re-reads a referenced original that was edited in place, so its media keeps its
+    /// id and takes the new hash and metadata. Everything derived from the old bytes,
+    /// including thumbnails and streams, is dropped first so nothing reuses it.
+    async fn refresh_media(
+        pool: &PgPool,
+        user: &UserModel,
+        media: &MediaModel,
+    ) -> Result<(WatchedFileStatusEnum, Option<i32>), AppError> {
+        let derived = [
+            FileService::derived_path(&media.filepath, RENDITION_DIR, "jpg"),
+            FileService::derived_path(&media.filepath, MOTION_DIR, "mp4"),
+            FileService::derived_path(&media.filepath, POSTER_DIR, "jpg"),
+            FileService::derived_path(&media.filepath, SPRITE_DIR, "jpg"),
+            FileService::derived_path(&media.filepath, SPRITE_DIR, "vtt"),
+            FileService::derived_path(&media.filepath, PLAYBACK_DIR, "mp4"),
+        ];
+
+        for path in derived.into_iter().flatten() {
+            let _ = fs::remove_file(path);
+        }
+
+        let renditions = ThumbnailService::delete_renditions(pool, media.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        for rendition in renditions {
+            let _ = fs::remove_file(rendition.filepath);
+        }
+
+        HlsService::delete_stream(pool, media.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if let Some(directory) = FileService::derived_path(&media.filepath, HLS_DIR, "") {
+            let _ = fs::remove_dir_all(directory);
+        }
+
+        let metadata = ReextractService::reextract_media_metadata(pool, media).await?;
+
+        let media = MediaService::media_detail(pool, media.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        UploadService::spawn_derivatives(pool, user, &media, metadata.duration);
+
+        Ok((WatchedFileStatusEnum::Imported, Some(media.id)))
+    }
+
+    /// Retires a file that is no longer in the folder. Media referencing it is removed
+    /// along with its now dangling link; files that were moved in are left alone, as is
+    /// anything still on disk but out of sight, e.g. in a directory that became
+    /// unreadable. Returns whether anything was removed.
+    async fn retire_file(
+        pool: &PgPool,
+        record: &WatchedFileModel,
+        source: &Path,
+    ) -> Result<bool, String> {
+        let gone = fs::symlink_metadata(source).is_err_and(|e| e.kind() == io::ErrorKind::NotFound);
+
+        if !gone {
+            return Ok(false);
+        }
+
+        let Some(media) = Self::find_referencing_media(pool, record, source).await else {
+            return Ok(false);
+        };
+
+        MediaService::delete_media(pool, media.id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let _ = fs::remove_file(&media.filepath);
+
+        WatchedFolderService::delete_file(pool, record.id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(true)
+    }
+
+    fn status_label(status: &WatchedFileStatusEnum) -> &'static str {
+        match status {
+            WatchedFileStatusEnum::Imported => "imported",
+            WatchedFileStatusEnum::Duplicate => "duplicates",
+            WatchedFileStatusEnum::Skipped => "skipped",
+            _ => "failed",
+        }
+    }
+
+    /// Every regular file under `root`. Hidden entries, where Samba and most copy tools
+    /// keep files in flight, and symlinks are passed over.
+    fn walk(root: &Path) -> io::Result<Vec<WatchedFileEntry>> {
+        let mut files = Vec::new();
+        let mut directories = vec![root.to_path_buf()];
+        let mut first = true;
+
+        while let Some(directory) = directories.pop() {
+            let entries = match fs::read_dir(&directory) {
+                Ok(entries) => entries,
+                Err(e) if first => return Err(e),
+                Err(_) => continue,
+            };
+            first = false;
+
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let absolute = entry.path();
+
+                let (Ok(file_type), Some(name)) = (entry.file_type(), absolute.file_name()) else {
+                    continue;
+                };
+
+                if name.to_string_lossy().starts_with('.') || file_type.is_symlink() {
+                    continue;
+                }
+
+                if file_type.is_dir() {
+                    directories.push(absolute);
+                    continue;
+                }
+
+                let (Ok(metadata), Some(path)) = (
+                    entry.metadata(),
+                    absolute
+                        .strip_prefix(root)
+                        .ok()
+                        .and_then(|path| path.to_str())
+                        .map(str::to_string),
+                ) else {
+                    continue;
+                };
+
+                let Ok(modified) = metadata.modified() else {
+                    continue;
+                };
+
+                files.push(WatchedFileEntry {
+                    path,
+                    absolute,
+                    size: metadata.len() as i64,
+                    // The database keeps microseconds, and the stored time must compare
+                    // equal on the next scan.
+                    modified_at: DateTime::<Utc>::from(modified).trunc_subsecs(6),
+                });
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Runs a file through the upload pipeline, after checking it is not already in the
+    /// library, and returns its status with the media it became or duplicates.
+    async fn import_file(
+        pool: &PgPool,
+        user: &UserModel,
+        folder: &WatchedFolderModel,
+        file: &WatchedFileEntry,
+    ) -> Result<(WatchedFileStatusEnum, Option<i32>), AppError> {
+        let (Some(source), Some(name)) = (
+            file.absolute.to_str(),
+            file.absolute.file_name().and_then(|name| name.to_str()),
+        ) else {
+            return Ok((WatchedFileStatusEnum::Skipped, None));
+        };
+
+        if XmpService::is_sidecar(name) {
+            return Self::import_sidecar(pool, user, folder, file, source).await;
+        }
+
+        UserService::create_user_directory(user).await?;
+
+        let file_name = FileService::sanitize_filename(name);
+        let destination = format!("./uploads/{}/{}", user.uuid, file_name);
+
+        let path = source.to_string();
+        let hash = tokio::task::spawn_blocking(move || FileService::generate_file_hash(&path))
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))??;
+
+        let existing = MediaService::find_media_by_hash(pool, user.id, &hash)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if let Some(media) = existing {
+            return Ok((WatchedFileStatusEnum::Duplicate, Some(media.id)));
+        }
+
+        let mime_type = MediaMetadataService::detect_mime_type(source, name)?;
+
+        if MediaTypeEnum::from_mime(&mime_type) == MediaTypeEnum::Unknown {
+            return Ok((WatchedFileStatusEnum::Skipped, None));
+        }
+
+        Self::place(&folder.mode, source, &destination)?;
+
+        match UploadService::ingest_file(pool, user, &file_name, &destination, name).await {
+            Ok(media) => Ok((WatchedFileStatusEnum::Imported, Some(media.id))),
+            Err(e) => {
+                Self::unplace(&folder.mode, source, &destination);
+                Err(e)
+            }
+        }
+    }
+
+    /// Pairs a sidecar with media imported from beside it in the same folder, never
+    /// with same-named media from elsewhere. Sidecars with no such media stay put.
+    async fn import_sidecar(
+        pool: &PgPool,
+        user: &UserModel,
+        folder: &WatchedFolderModel,
+        file: &WatchedFileEntry,
+        source: &str,
+    ) -> Result<(WatchedFileStatusEnum, Option<i32>), AppError> {
+        let base = &file.path[..file.path.len() - 4];
+
+        let media_id = WatchedFolderService::find_sidecar_media_id(pool, folder.id, base)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let Some(media_id) = media_id else {
+            return Ok((WatchedFileStatusEnum::Skipped, None));
+        };
+
+        let media = MediaService::media_detail(pool, media_id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let destination = XmpService::sidecar_path(&media.filepath)
+            .ok_or_else(|| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if let Some(parent) = Path::new(&destination).parent() {
+            fs::create_dir_all(parent)
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        // A referenced sidecar edited in place is already linked.
+        let linked = fs::read_link(&destination).is_ok_and(|target| target == Path::new(source));
+
+        if !linked {
+            Self::place(&folder.mode, source, &destination)?;
+        }
+
+        match XmpService::apply_sidecar(pool, user, &media).await {
+            Ok(()) => Ok((WatchedFileStatusEnum::Imported, Some(media.id))),
+            Err(e) => {
+                if !linked {
+                    Self::unplace(&folder.mode, source, &destination);
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Puts the file in the user's upload directory. A referenced file is linked rather
+    /// than copied, so renditions and sidecars are written next to the link and never
+    /// into the watched folder.
+    fn place(
+        mode: &WatchedFolderModeEnum,
+        source: &str,
+        destination: &str,
+    ) -> Result<(), AppError> {
+        let result = match mode {
+            WatchedFolderModeEnum::Move => Self::move_file(source, destination),
+            WatchedFolderModeEnum::Reference => std::os::unix::fs::symlink(source, destination),
+        };
+
+        result.map_err(|_| AppError::InternalServerError("Failed to place file".into()))
+    }
+
+    fn unplace(mode: &WatchedFolderModeEnum, source: &str, destination: &str) {
+        let _ = match mode {
+            WatchedFolderModeEnum::Move => Self::move_file(destination, source),
+            WatchedFolderModeEnum::Reference => fs::remove_file(destination),
+        };
+    }
+
+    /// Renames when both sides share a filesystem, which a network share and the
+    /// upload directory rarely do.
+    fn move_file(source: &str, destination: &str) -> io::Result<()> {
+        if fs::rename(source, destination).is_ok() {
+            return Ok(());
+        }
+
+        fs::copy(source, destination)?;
+        fs::remove_file(source)
+    }
+}
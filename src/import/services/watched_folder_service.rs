@@ -0,0 +1,206 @@
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::auth::services::AuthService;
+use crate::import::{
+    enums::{
+        watched_file_status_enum::WatchedFileStatusEnum,
+        watched_folder_mode_enum::WatchedFolderModeEnum,
+    },
+    models::{WatchedFileModel, WatchedFolderModel},
+    services::WatchedFileEntry,
+};
+
+pub struct WatchedFolderService {}
+
+impl WatchedFolderService {
+    pub async fn create_folder(
+        pool: &PgPool,
+        user_id: i32,
+        path: &str,
+        mode: WatchedFolderModeEnum,
+    ) -> Result<WatchedFolderModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            WatchedFolderModel,
+            r#"insert into watched_folders (user_id, path, mode, created_at, updated_at, created_by, updated_by) values ($1, $2, $3, $4, $4, $5, $5) returning *"#,
+            user_id,
+            path,
+            mode as i32,
+            now,
+            actor_id,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn list_folders(pool: &PgPool) -> Result<Vec<WatchedFolderModel>, sqlx::Error> {
+        sqlx::query_as!(
+            WatchedFolderModel,
+            r#"select * from watched_folders where deleted_at is null order by id"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn list_enabled_folders(
+        pool: &PgPool,
+    ) -> Result<Vec<WatchedFolderModel>, sqlx::Error> {
+        sqlx::query_as!(
+            WatchedFolderModel,
+            r#"select * from watched_folders where deleted_at is null and enabled order by id"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn update_folder(
+        pool: &PgPool,
+        id: i32,
+        mode: Option<WatchedFolderModeEnum>,
+        enabled: Option<bool>,
+    ) -> Result<Option<WatchedFolderModel>, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            WatchedFolderModel,
+            r#"update watched_folders set mode = coalesce($1, mode), enabled = coalesce($2, enabled), updated_at = $3, updated_by = $4 where deleted_at is null and id = $5 returning *"#,
+            mode.map(|mode| mode as i32),
+            enabled,
+            now,
+            actor_id,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn delete_folder(
+        pool: &PgPool,
+        id: i32,
+    ) -> Result<Option<WatchedFolderModel>, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            WatchedFolderModel,
+            r#"update watched_folders set deleted_at = $1, updated_at = $1, updated_by = $2 where deleted_at is null and id = $3 returning *"#,
+            now,
+            actor_id,
+            id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Records the outcome of a scan; `error` is cleared by the next one that succeeds.
+    pub async fn mark_scanned(
+        pool: &PgPool,
+        id: i32,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"update watched_folders set last_scanned_at = $1, error = $2 where id = $3"#,
+            now,
+            error,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_files(
+        pool: &PgPool,
+        watched_folder_id: i32,
+    ) -> Result<Vec<WatchedFileModel>, sqlx::Error> {
+        sqlx::query_as!(
+            WatchedFileModel,
+            r#"select * from watched_files where deleted_at is null and watched_folder_id = $1"#,
+            watched_folder_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Remembers what became of a file so rescans pass over it until it changes.
+    pub async fn record_file(
+        pool: &PgPool,
+        watched_folder_id: i32,
+        file: &WatchedFileEntry,
+        status: WatchedFileStatusEnum,
+        media_id: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<WatchedFileModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            WatchedFileModel,
+            r#"insert into watched_files (watched_folder_id, path, size, modified_at, status, media_id, error, created_at, updated_at, created_by, updated_by)
+               values ($1, $2, $3, $4, $5, $6, $7, $8, $8, $9, $9)
+               on conflict (watched_folder_id, path) do update set size = excluded.size, modified_at = excluded.modified_at, status = excluded.status, media_id = excluded.media_id, error = excluded.error, updated_at = excluded.updated_at, updated_by = excluded.updated_by, deleted_at = null
+               returning *"#,
+            watched_folder_id,
+            file.path,
+            file.size,
+            file.modified_at,
+            status as i32,
+            media_id,
+            error,
+            now,
+            actor_id,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Forgets a file that has left the folder.
+    pub async fn delete_file(pool: &PgPool, id: i32) -> Result<(), sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"update watched_files set deleted_at = $1, updated_at = $1, updated_by = $2 where id = $3"#,
+            now,
+            actor_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Media imported, not merely matched as a duplicate, from the same folder and directory as a sidecar whose path,
+    /// `.xmp` dropped, is `base`: either the original's full path or its stem.
+    pub async fn find_sidecar_media_id(
+        pool: &PgPool,
+        watched_folder_id: i32,
+        base: &str,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let record = sqlx::query!(
+            r#"
+                select media_id from watched_files
+                where deleted_at is null and watched_folder_id = $1 and status = $3
+                and lower(path) not like '%.xmp'
+                and (lower(path) = lower($2) or lower(regexp_replace(path, '\.[^./]*$', '')) = lower($2))
+                order by lower(path) = lower($2) desc, id desc
+                limit 1
+            "#,
+            watched_folder_id,
+            base,
+            WatchedFileStatusEnum::Imported as i32
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(record.and_then(|record| record.media_id))
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "job_status", rename_all = "lowercase")]
+pub enum JobStatusEnum {
+    Pending = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+}
+
+impl From<i32> for JobStatusEnum {
+    fn from(status: i32) -> Self {
+        match status {
+            1 => JobStatusEnum::Running,
+            2 => JobStatusEnum::Completed,
+            3 => JobStatusEnum::Failed,
+            _ => JobStatusEnum::Pending,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "job_type", rename_all = "lowercase")]
+pub enum JobTypeEnum {
+    Unknown = 0,
+    ReextractMetadata = 1,
+    DetectStacks = 2,
+    TranscodePlayback = 3,
+    ExportAccount = 4,
+    ImportTakeout = 5,
+}
+
+impl From<i32> for JobTypeEnum {
+    fn from(job_type: i32) -> Self {
+        match job_type {
+            1 => JobTypeEnum::ReextractMetadata,
+            2 => JobTypeEnum::DetectStacks,
+            3 => JobTypeEnum::TranscodePlayback,
+            4 => JobTypeEnum::ExportAccount,
+            5 => JobTypeEnum::ImportTakeout,
+            _ => JobTypeEnum::Unknown,
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod job_status_enum;
+pub mod job_type_enum;
@@ -0,0 +1,36 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::errors::app_error::AppError;
+use crate::job::{models::JobModel, services::JobService};
+use crate::user::models::UserModel;
+
+pub async fn get_job_list(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+) -> Result<Json<Vec<JobModel>>, AppError> {
+    let jobs = JobService::list_jobs(&state.db, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(jobs))
+}
+
+pub async fn get_job_detail(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+) -> Result<Json<JobModel>, AppError> {
+    let job = JobService::find_job(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    match job {
+        Some(job) => Ok(Json(job)),
+        None => Err(AppError::NotFound("Job not found".into())),
+    }
+}
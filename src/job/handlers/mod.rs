@@ -0,0 +1,3 @@
+pub mod job_handler;
+
+pub use job_handler::*;
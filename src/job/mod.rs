@@ -0,0 +1,5 @@
+pub mod enums;
+pub mod handlers;
+pub mod models;
+pub mod routes;
+pub mod services;
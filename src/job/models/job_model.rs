@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::job::enums::{job_status_enum::JobStatusEnum, job_type_enum::JobTypeEnum};
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct JobModel {
+    pub id: i32,
+    pub uuid: Uuid,
+
+    pub user_id: i32,
+    pub job_type: JobTypeEnum,
+    pub status: JobStatusEnum,
+    pub payload: Option<serde_json::Value>,
+    pub result: Option<serde_json::Value>,
+    pub progress: i32,
+    pub total: i32,
+    pub error: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
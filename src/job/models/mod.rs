@@ -0,0 +1,3 @@
+pub mod job_model;
+
+pub use job_model::*;
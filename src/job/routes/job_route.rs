@@ -0,0 +1,17 @@
+use axum::{middleware, routing::get, Router};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::auth::middlewares::auth_middleware;
+use crate::job::handlers::{get_job_detail, get_job_list};
+
+pub fn job_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/jobs", get(get_job_list))
+        .route("/jobs/{id}", get(get_job_detail))
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state)
+}
@@ -0,0 +1,3 @@
+pub mod job_route;
+
+pub use job_route::*;
@@ -0,0 +1,202 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use std::future::Future;
+use tracing::{error, info};
+
+use crate::auth::services::AuthService;
+use crate::errors::app_error::AppError;
+use crate::job::{
+    enums::{job_status_enum::JobStatusEnum, job_type_enum::JobTypeEnum},
+    models::JobModel,
+};
+use crate::user::models::UserModel;
+
+pub struct JobService {}
+
+impl JobService {
+    pub async fn create_job(
+        pool: &PgPool,
+        user: &UserModel,
+        job_type: JobTypeEnum,
+        payload: Option<serde_json::Value>,
+    ) -> Result<JobModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            JobModel,
+            r#"insert into jobs (user_id, job_type, status, payload, created_at, updated_at, created_by, updated_by) values ($1, $2, $3, $4, $5, $5, $6, $6) returning *"#,
+            user.id,
+            job_type as i32,
+            JobStatusEnum::Pending as i32,
+            payload,
+            now,
+            actor_id,
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_job(
+        pool: &PgPool,
+        id: i32,
+        user_id: i32,
+    ) -> Result<Option<JobModel>, sqlx::Error> {
+        sqlx::query_as!(
+            JobModel,
+            r#"select * from jobs where deleted_at is null and id = $1 and user_id = $2"#,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Pending or running job of `job_type` whose payload contains `payload`.
+    pub async fn find_active_job(
+        pool: &PgPool,
+        user_id: i32,
+        job_type: JobTypeEnum,
+        payload: &serde_json::Value,
+    ) -> Result<Option<JobModel>, sqlx::Error> {
+        sqlx::query_as!(
+            JobModel,
+            r#"
+                select * from jobs
+                where deleted_at is null and user_id = $1 and job_type = $2 and payload @> $3
+                and status in ($4, $5)
+                order by id desc limit 1
+            "#,
+            user_id,
+            job_type as i32,
+            payload,
+            JobStatusEnum::Pending as i32,
+            JobStatusEnum::Running as i32,
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn list_jobs(pool: &PgPool, user_id: i32) -> Result<Vec<JobModel>, sqlx::Error> {
+        sqlx::query_as!(
+            JobModel,
+            r#"select * from jobs where deleted_at is null and user_id = $1 order by id desc"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn mark_running(pool: &PgPool, id: i32, total: i32) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"update jobs set status = $1, total = $2, progress = 0, started_at = $3, updated_at = $3 where id = $4"#,
+            JobStatusEnum::Running as i32,
+            total,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_progress(pool: &PgPool, id: i32, progress: i32) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"update jobs set progress = $1, updated_at = $2 where id = $3"#,
+            progress,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_completed(
+        pool: &PgPool,
+        id: i32,
+        result: Option<serde_json::Value>,
+    ) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"update jobs set status = $1, result = $2, finished_at = $3, updated_at = $3 where id = $4"#,
+            JobStatusEnum::Completed as i32,
+            result,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_failed(pool: &PgPool, id: i32, error: &str) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"update jobs set status = $1, error = $2, finished_at = $3, updated_at = $3 where id = $4"#,
+            JobStatusEnum::Failed as i32,
+            error,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Fails jobs left pending or running by a previous run, whose tasks died with it,
+    /// so they no longer block the same job from being requested again.
+    pub async fn fail_interrupted_jobs(pool: &PgPool) -> Result<u64, sqlx::Error> {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+                update jobs set status = $1, error = 'Interrupted by a server restart', finished_at = $2, updated_at = $2
+                where deleted_at is null and status in ($3, $4)
+            "#,
+            JobStatusEnum::Failed as i32,
+            now,
+            JobStatusEnum::Pending as i32,
+            JobStatusEnum::Running as i32,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Runs `task` in the background as `user`, recording its outcome on the job row.
+    pub fn spawn<F>(pool: PgPool, user: UserModel, job: &JobModel, task: F)
+    where
+        F: Future<Output = Result<Option<serde_json::Value>, AppError>> + Send + 'static,
+    {
+        let job_id = job.id;
+
+        tokio::spawn(AuthService::login(user, async move {
+            info!("Job started: job_id={}", job_id);
+
+            let outcome = match task.await {
+                Ok(result) => Self::mark_completed(&pool, job_id, result).await,
+                Err(e) => {
+                    error!("Job failed: job_id={}, reason={:?}", job_id, e);
+
+                    Self::mark_failed(&pool, job_id, &format!("{:?}", e)).await
+                }
+            };
+
+            if let Err(e) = outcome {
+                error!("Failed to save job status: job_id={}, reason={}", job_id, e);
+            }
+        }));
+    }
+}
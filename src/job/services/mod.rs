@@ -0,0 +1,3 @@
+pub mod job_service;
+
+pub use job_service::*;
@@ -8,7 +8,12 @@ mod app;
 mod auth;
 mod config;
 mod errors;
+mod export;
+mod import;
+mod job;
 mod media;
+mod stack;
+mod tag;
 mod test;
 mod user;
 mod utility;
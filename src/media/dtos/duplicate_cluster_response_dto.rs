@@ -0,0 +1,33 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::media::enums::media_type_enum::MediaTypeEnum;
+
+#[derive(Serialize)]
+pub struct DuplicateClusterResponseDto {
+    pub threshold: u32,
+    pub clusters: Vec<DuplicateClusterDto>,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateClusterDto {
+    pub suggested_media_id: i32,
+    pub media: Vec<DuplicateMediaRow>,
+}
+
+#[derive(Serialize)]
+pub struct DuplicateMediaRow {
+    pub id: i32,
+    pub uuid: Uuid,
+    pub filename: String,
+    pub media_type: MediaTypeEnum,
+    pub original_filename: Option<String>,
+    pub mime_type: Option<String>,
+    pub size: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub camera_make: Option<String>,
+    pub taken_at: Option<NaiveDateTime>,
+    pub perceptual_hash: Option<String>,
+}
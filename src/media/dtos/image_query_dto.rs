@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+use crate::media::enums::{image_fit_enum::ImageFitEnum, image_format_enum::ImageFormatEnum};
+
+#[derive(Deserialize)]
+pub struct ImageQueryDto {
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+    pub fit: Option<ImageFitEnum>,
+    pub format: Option<ImageFormatEnum>,
+    /// 1-100, for JPEG and AVIF. WebP output is always lossless and rejects it.
+    pub quality: Option<u8>,
+}
@@ -0,0 +1,9 @@
+use chrono::NaiveDateTime;
+
+pub struct MediaArchiveEntryDto {
+    pub id: i32,
+    pub filename: String,
+    pub filepath: String,
+    pub original_filename: Option<String>,
+    pub taken_at: Option<NaiveDateTime>,
+}
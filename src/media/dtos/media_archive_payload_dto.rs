@@ -0,0 +1,12 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Selects media by id, by tag, by capture date or any combination; at least one is
+/// required. Dates compare against `taken_at` and are inclusive.
+#[derive(Serialize, Deserialize)]
+pub struct MediaArchivePayloadDto {
+    pub media_ids: Option<Vec<i32>>,
+    pub tag_id: Option<i32>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct MediaBulkUpdatePayloadDto {
+    pub media_ids: Vec<i32>,
+    pub favorite: Option<bool>,
+    pub rating: Option<i16>,
+    pub archived: Option<bool>,
+}
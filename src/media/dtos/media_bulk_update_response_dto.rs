@@ -0,0 +1,6 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct MediaBulkUpdateResponseDto {
+    pub affected: u64,
+}
@@ -4,8 +4,9 @@ use uuid::Uuid;
 
 use crate::media::{
     enums::media_type_enum::MediaTypeEnum,
-    models::{MediaMetadataModel, MediaModel},
+    models::{MediaMetadataModel, MediaMetadataOverrideModel, MediaModel},
 };
+use crate::tag::models::TagModel;
 
 #[derive(Serialize)]
 pub struct MediaDetailResponseDto {
@@ -15,16 +16,37 @@ pub struct MediaDetailResponseDto {
     pub filename: String,
     pub filepath: String,
     pub media_type: MediaTypeEnum,
+    pub favorite: bool,
+    pub rating: i16,
+    pub archived: bool,
+    pub stack_id: Option<i32>,
+    pub motion_media_id: Option<i32>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
     pub created_by: Option<i32>,
     pub updated_by: Option<i32>,
     pub metadata: Option<MediaMetadataModel>,
+    pub overrides: Option<MediaMetadataOverrideModel>,
+    pub tags: Vec<TagModel>,
 }
 
-impl From<(MediaModel, MediaMetadataModel)> for MediaDetailResponseDto {
-    fn from((media, metadata): (MediaModel, MediaMetadataModel)) -> Self {
+impl
+    From<(
+        MediaModel,
+        MediaMetadataModel,
+        Option<MediaMetadataOverrideModel>,
+        Vec<TagModel>,
+    )> for MediaDetailResponseDto
+{
+    fn from(
+        (media, metadata, overrides, tags): (
+            MediaModel,
+            MediaMetadataModel,
+            Option<MediaMetadataOverrideModel>,
+            Vec<TagModel>,
+        ),
+    ) -> Self {
         Self {
             id: media.id,
             uuid: media.uuid,
@@ -32,12 +54,19 @@ impl From<(MediaModel, MediaMetadataModel)> for MediaDetailResponseDto {
             filename: media.filename,
             filepath: media.filepath,
             media_type: media.media_type,
+            favorite: media.favorite,
+            rating: media.rating,
+            archived: media.archived,
+            stack_id: media.stack_id,
+            motion_media_id: media.motion_media_id,
             created_at: media.created_at,
             updated_at: media.updated_at,
             deleted_at: media.deleted_at,
             created_by: media.created_by,
             updated_by: media.updated_by,
             metadata: Some(metadata),
+            overrides,
+            tags,
         }
     }
 }
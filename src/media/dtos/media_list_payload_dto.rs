@@ -4,4 +4,12 @@ use serde::Deserialize;
 pub struct MediaListPayloadDto {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub tag_id: Option<i32>,
+    pub favorite: Option<bool>,
+    pub min_rating: Option<i16>,
+    pub archived: Option<bool>,
+    pub expand_stacks: Option<bool>,
+    pub lens_model: Option<String>,
+    pub min_iso: Option<i32>,
+    pub max_iso: Option<i32>,
 }
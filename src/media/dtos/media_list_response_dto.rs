@@ -19,6 +19,17 @@ pub struct MediaListRow {
     pub filename: String,
     pub filepath: String,
     pub media_type: MediaTypeEnum,
+    pub favorite: bool,
+    pub rating: i16,
+    pub archived: bool,
+    pub stack_id: Option<i32>,
+    pub motion_media_id: Option<i32>,
+
+    /// Upright dimensions and a BlurHash, so the grid can lay out and paint a
+    /// placeholder before the thumbnail arrives.
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub blurhash: Option<String>,
 
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -27,4 +38,7 @@ pub struct MediaListRow {
     pub updated_by: Option<i32>,
 
     pub media_metadata: Option<serde_json::Value>,
+    pub metadata_overrides: Option<serde_json::Value>,
+    pub tags: Option<serde_json::Value>,
+    pub stack_count: Option<i64>,
 }
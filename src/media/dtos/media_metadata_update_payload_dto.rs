@@ -0,0 +1,22 @@
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+use crate::utility::serde::double_option;
+
+/// Omitted fields are left untouched, `null` clears the override and falls back to the
+/// extracted value.
+#[derive(Deserialize)]
+pub struct MediaMetadataUpdatePayloadDto {
+    #[serde(default, deserialize_with = "double_option")]
+    pub title: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub taken_at: Option<Option<DateTime<FixedOffset>>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub latitude: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub longitude: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub location_name: Option<Option<String>>,
+}
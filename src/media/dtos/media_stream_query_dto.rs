@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+use crate::media::enums::media_variant_enum::MediaVariantEnum;
+
+#[derive(Deserialize)]
+pub struct MediaStreamQueryDto {
+    pub variant: Option<MediaVariantEnum>,
+}
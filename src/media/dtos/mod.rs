@@ -1,13 +1,35 @@
+pub mod duplicate_cluster_response_dto;
+pub mod duplicate_query_dto;
+pub mod image_query_dto;
+pub mod media_archive_entry_dto;
+pub mod media_archive_payload_dto;
+pub mod media_bulk_update_payload_dto;
+pub mod media_bulk_update_response_dto;
 pub mod media_detail_response_dto;
 pub mod media_download_payload_dto;
 pub mod media_list_payload_dto;
 pub mod media_list_response_dto;
+pub mod media_metadata_update_payload_dto;
+pub mod media_stream_query_dto;
 pub mod pagination_metadat_dto;
+pub mod reextract_metadata_payload_dto;
+pub mod thumbnail_query_dto;
 pub mod upload_response_dto;
 
+pub use duplicate_cluster_response_dto::*;
+pub use duplicate_query_dto::*;
+pub use image_query_dto::*;
+pub use media_archive_entry_dto::*;
+pub use media_archive_payload_dto::*;
+pub use media_bulk_update_payload_dto::*;
+pub use media_bulk_update_response_dto::*;
 pub use media_detail_response_dto::*;
 pub use media_download_payload_dto::*;
 pub use media_list_payload_dto::*;
 pub use media_list_response_dto::*;
+pub use media_metadata_update_payload_dto::*;
+pub use media_stream_query_dto::*;
 pub use pagination_metadat_dto::*;
+pub use reextract_metadata_payload_dto::*;
+pub use thumbnail_query_dto::*;
 pub use upload_response_dto::*;
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+/// Leaving `media_ids` out re-extracts the whole library.
+#[derive(Serialize, Deserialize)]
+pub struct ReextractMetadataPayloadDto {
+    pub media_ids: Option<Vec<i32>>,
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFitEnum {
+    /// Fits inside the box, keeping the aspect ratio. Never upscales.
+    #[default]
+    Contain,
+    /// Fills the box and crops the overflow around the centre.
+    Cover,
+    /// Stretches to exactly the box.
+    Fill,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormatEnum {
+    #[default]
+    Jpeg,
+    /// Encoded losslessly; `quality` does not apply.
+    Webp,
+    Avif,
+}
+
+impl ImageFormatEnum {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormatEnum::Jpeg => "jpg",
+            ImageFormatEnum::Webp => "webp",
+            ImageFormatEnum::Avif => "avif",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageFormatEnum::Jpeg => "image/jpeg",
+            ImageFormatEnum::Webp => "image/webp",
+            ImageFormatEnum::Avif => "image/avif",
+        }
+    }
+}
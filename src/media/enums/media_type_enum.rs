@@ -1,12 +1,21 @@
 use serde::{Deserialize, Serialize};
 use sqlx::Type;
 
+const DOCUMENT_MIME_TYPES: [&str; 4] = [
+    "application/pdf",
+    "application/msword",
+    "application/rtf",
+    "application/epub+zip",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
 #[sqlx(type_name = "media_type", rename_all = "lowercase")]
 pub enum MediaTypeEnum {
     Unknown = 0,
     Photo = 1,
     Video = 2,
+    Audio = 3,
+    Document = 4,
 }
 
 impl From<i32> for MediaTypeEnum {
@@ -14,6 +23,8 @@ impl From<i32> for MediaTypeEnum {
         match media_type {
             1 => MediaTypeEnum::Photo,
             2 => MediaTypeEnum::Video,
+            3 => MediaTypeEnum::Audio,
+            4 => MediaTypeEnum::Document,
             _ => MediaTypeEnum::Unknown,
         }
     }
@@ -25,6 +36,14 @@ impl MediaTypeEnum {
             MediaTypeEnum::Photo
         } else if mime.starts_with("video/") {
             MediaTypeEnum::Video
+        } else if mime.starts_with("audio/") {
+            MediaTypeEnum::Audio
+        } else if DOCUMENT_MIME_TYPES.contains(&mime)
+            || mime.starts_with("application/vnd.openxmlformats-officedocument.")
+            || mime.starts_with("application/vnd.oasis.opendocument.")
+            || mime.starts_with("application/vnd.ms-")
+        {
+            MediaTypeEnum::Document
         } else {
             MediaTypeEnum::Unknown
         }
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaVariantEnum {
+    #[default]
+    Original,
+    Rendition,
+    /// H.264/AAC MP4 of a video whose codecs or container browsers cannot play.
+    Playback,
+}
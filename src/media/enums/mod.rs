@@ -1 +1,5 @@
+pub mod image_fit_enum;
+pub mod image_format_enum;
 pub mod media_type_enum;
+pub mod media_variant_enum;
+pub mod stream_status_enum;
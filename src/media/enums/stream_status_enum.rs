@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "stream_status", rename_all = "lowercase")]
+pub enum StreamStatusEnum {
+    Pending = 0,
+    Processing = 1,
+    Ready = 2,
+    Failed = 3,
+}
+
+impl From<i32> for StreamStatusEnum {
+    fn from(status: i32) -> Self {
+        match status {
+            1 => StreamStatusEnum::Processing,
+            2 => StreamStatusEnum::Ready,
+            3 => StreamStatusEnum::Failed,
+            _ => StreamStatusEnum::Pending,
+        }
+    }
+}
@@ -1,25 +1,39 @@
 use axum::{
-    extract::{Multipart, Path, State},
-    response::Response,
+    extract::{Multipart, Path, Query, State},
+    response::{IntoResponse, Response},
     Extension, Json,
 };
-use hyper::HeaderMap;
+use hyper::{
+    header::{HeaderValue, VARY},
+    HeaderMap, Method, StatusCode,
+};
 use std::sync::Arc;
-use tokio::fs;
 
 use crate::app::AppState;
-use crate::errors::app_error::AppError;
+use crate::errors::{app_error::AppError, error_response_dto::ErrorResponseDto};
+use crate::job::{enums::job_type_enum::JobTypeEnum, models::JobModel, services::JobService};
 use crate::media::{
     dtos::{
-        MediaDetailResponseDto, MediaDownloadPayloadDto, MediaListPayloadDto, MediaListResponseDto,
-        UploadResponseDto,
+        DuplicateClusterResponseDto, DuplicateQueryDto, ImageQueryDto, MediaArchivePayloadDto,
+        MediaBulkUpdatePayloadDto, MediaBulkUpdateResponseDto, MediaDetailResponseDto,
+        MediaDownloadPayloadDto, MediaListPayloadDto, MediaListResponseDto,
+        MediaMetadataUpdatePayloadDto, MediaStreamQueryDto, ReextractMetadataPayloadDto,
+        ThumbnailQueryDto, UploadResponseDto,
+    },
+    enums::{
+        media_type_enum::MediaTypeEnum, media_variant_enum::MediaVariantEnum,
+        stream_status_enum::StreamStatusEnum,
     },
-    enums::media_type_enum::MediaTypeEnum,
+    models::{MediaMetadataAuditModel, MediaMetadataOverrideModel},
     services::{
-        DownloadService, MediaMetadataService, MediaService, PhotoService, UploadService,
-        VideoService,
+        DownloadService, DuplicateService, FileService, HeifService, HlsService, ImageService,
+        MediaMetadataOverrideService, MediaMetadataService, MediaService, MotionPhotoService,
+        PhotoService, PlaybackService, RangeService, RawService, ReextractService,
+        ThumbnailService, UploadService, VideoPreviewService, DEFAULT_THUMBNAIL_PRESET,
+        HLS_MASTER_PLAYLIST, PLAYBACK_DIR, RENDER_SLOTS,
     },
 };
+use crate::tag::services::TagService;
 use crate::user::models::UserModel;
 
 pub async fn upload_chunk(
@@ -62,55 +76,88 @@ pub async fn get_thumbnail(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<UserModel>,
     Path(id): Path<i32>,
+    Query(query): Query<ThumbnailQueryDto>,
+    headers: HeaderMap,
 ) -> Result<Response, AppError> {
     let media = MediaService::check_media_access(&state.db, id, user.id)
         .await
         .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
 
-    if media.media_type != MediaTypeEnum::Photo && media.media_type != MediaTypeEnum::Video {
-        return Err(AppError::NotFound("Thumbnail not found".into()));
+    let size = query.size.as_deref().unwrap_or(DEFAULT_THUMBNAIL_PRESET);
+
+    let preset = ThumbnailService::find_preset(&state.db, size)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .ok_or_else(|| AppError::BadRequest(format!("Unknown thumbnail size: {}", size)))?;
+
+    let rendition =
+        ThumbnailService::get_or_generate_rendition(&state.db, &media, &user, &preset).await;
+
+    match (rendition, &media.media_type) {
+        (Ok(rendition), _) => {
+            let etag = ThumbnailService::etag(&rendition);
+
+            if RangeService::etag_matches(&headers, &etag) {
+                return DownloadService::not_modified(&etag);
+            }
+
+            DownloadService::download_thumbnail(&rendition.filepath, &rendition.content_type, &etag)
+                .await
+        }
+        (Err(e), MediaTypeEnum::Photo | MediaTypeEnum::Video) => Err(e),
+        (Err(_), media_type) => DownloadService::download_icon(ThumbnailService::icon(media_type)),
     }
+}
+
+pub async fn get_image(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    Query(query): Query<ImageQueryDto>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    ImageService::validate(&query)?;
 
-    let stem = std::path::Path::new(&media.filename)
-        .file_stem() // gets the filename without extension
-        .and_then(|s| s.to_str())
-        .ok_or("Invalid filename")
+    let media = MediaService::check_media_access(&state.db, id, user.id)
+        .await
         .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
 
-    let mut thumbnail_path = format!("./uploads/{}/thumbnails/{}.webp", user.uuid, stem);
+    if media.media_type != MediaTypeEnum::Photo {
+        return Err(AppError::BadRequest("Media is not a photo".into()));
+    }
 
-    if !fs::try_exists(thumbnail_path.clone())
+    let hash = MediaMetadataService::get_metadata_for_media(&state.db, media.id)
         .await
-        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
-    {
-        if media.media_type == MediaTypeEnum::Photo {
-            thumbnail_path = PhotoService::generate_photo_thumbnail(
-                &media.filepath,
-                &media.filename,
-                400,
-                &user,
-            )
-            .await
-            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
-        } else if media.media_type == MediaTypeEnum::Video {
-            thumbnail_path = VideoService::generate_video_thumbnail(
-                &media.filepath,
-                &media.filename,
-                400,
-                &user,
-            )
+        .ok()
+        .and_then(|metadata| metadata.hash);
+
+    let content_type = query.format.unwrap_or_default().content_type();
+    let image = match ImageService::find_cached(&media.filepath, hash.as_deref(), &query) {
+        Some(image) => image,
+        None => {
+            let _slot = RENDER_SLOTS.acquire().await;
+            let filepath = media.filepath.clone();
+
+            tokio::task::spawn_blocking(move || {
+                ImageService::get_or_render(&filepath, hash.as_deref(), &query)
+                    .map_err(|e| e.to_string())
+            })
             .await
-            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+            .map_err(|_| AppError::InternalServerError("Failed to render image".into()))?
         }
-    }
+    };
 
-    DownloadService::download_thumbnail(&thumbnail_path).await
+    DownloadService::stream_file(&image, content_type, &method, &headers).await
 }
 
 pub async fn stream_media(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<UserModel>,
     Path(id): Path<i32>,
+    Query(query): Query<MediaStreamQueryDto>,
+    method: Method,
     headers: HeaderMap,
 ) -> Result<Response, AppError> {
     let media = MediaService::check_media_access(&state.db, id, user.id)
@@ -121,11 +168,263 @@ pub async fn stream_media(
         .await
         .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
 
-    let response = DownloadService::stream_media(media, metadata, headers).await?;
+    let mime_type = metadata.mime_type.clone().unwrap_or_default();
+
+    if HeifService::is_heif(&mime_type) || RawService::is_raw(&mime_type) {
+        let accept = headers.get("accept").and_then(|h| h.to_str().ok());
+
+        // No browser renders RAW, so its original is only sent when asked for explicitly.
+        let variant = query.variant.clone().unwrap_or(
+            match !RawService::is_raw(&mime_type) && HeifService::accepts_heif(accept) {
+                true => MediaVariantEnum::Original,
+                false => MediaVariantEnum::Rendition,
+            },
+        );
+
+        if variant == MediaVariantEnum::Rendition {
+            let filepath = media.filepath.clone();
+
+            let rendition = tokio::task::spawn_blocking(move || {
+                PhotoService::decodable_path(&filepath).map_err(|e| e.to_string())
+            })
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+            .map_err(|_| AppError::InternalServerError("Failed to decode image".into()))?;
+
+            let response =
+                DownloadService::stream_file(&rendition, "image/jpeg", &method, &headers).await?;
+
+            return Ok(vary_on_accept(response, &query));
+        }
+
+        let response = DownloadService::stream_media(media, metadata, &method, &headers).await?;
+
+        return Ok(vary_on_accept(response, &query));
+    }
+
+    if query.variant == Some(MediaVariantEnum::Playback)
+        && media.media_type == MediaTypeEnum::Video
+        && PlaybackService::needs_playback(&metadata)
+    {
+        let playback_path = FileService::derived_path(&media.filepath, PLAYBACK_DIR, "mp4")
+            .ok_or_else(|| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if std::path::Path::new(&playback_path).exists() {
+            return DownloadService::stream_file(&playback_path, "video/mp4", &method, &headers)
+                .await;
+        }
+
+        PlaybackService::request_playback(&state.db, &user, &media).await?;
+
+        return Ok(ErrorResponseDto::new(
+            StatusCode::ACCEPTED,
+            "Playback rendition is being prepared",
+        )
+        .into_response());
+    }
+
+    let response = DownloadService::stream_media(media, metadata, &method, &headers).await?;
 
     Ok(response)
 }
 
+pub async fn stream_motion(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let media = MediaService::check_media_access(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    if let Some(motion_media_id) = media.motion_media_id {
+        let video = MediaService::check_media_access(&state.db, motion_media_id, user.id)
+            .await
+            .map_err(|_| AppError::NotFound("Motion video not found".into()))?;
+
+        let metadata = MediaMetadataService::get_metadata_for_media(&state.db, video.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        return DownloadService::stream_media(video, metadata, &method, &headers).await;
+    }
+
+    let metadata = MediaMetadataService::get_metadata_for_media(&state.db, media.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    let length = metadata
+        .motion_video_length
+        .ok_or_else(|| AppError::NotFound("Media has no motion component".into()))?;
+
+    let filepath = media.filepath.clone();
+
+    let video = tokio::task::spawn_blocking(move || {
+        MotionPhotoService::extract_embedded_video(&filepath, length).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+    .map_err(|_| AppError::InternalServerError("Failed to extract motion video".into()))?;
+
+    DownloadService::stream_file(&video, "video/mp4", &method, &headers).await
+}
+
+pub async fn stream_poster(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let (filepath, duration) = video_preview_source(&state, &user, id).await?;
+
+    let poster = tokio::task::spawn_blocking(move || {
+        VideoPreviewService::generate_poster(&filepath, duration).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+    .map_err(|_| AppError::InternalServerError("Failed to generate poster".into()))?;
+
+    DownloadService::stream_file(&poster, "image/jpeg", &method, &headers).await
+}
+
+pub async fn stream_sprite(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let (sprite, _) = video_sprite(&state, &user, id).await?;
+
+    DownloadService::stream_file(&sprite, "image/jpeg", &method, &headers).await
+}
+
+pub async fn stream_sprite_track(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let (_, track) = video_sprite(&state, &user, id).await?;
+
+    DownloadService::stream_file(&track, "text/vtt", &method, &headers).await
+}
+
+async fn video_sprite(
+    state: &AppState,
+    user: &UserModel,
+    id: i32,
+) -> Result<(String, String), AppError> {
+    let (filepath, duration) = video_preview_source(state, user, id).await?;
+
+    tokio::task::spawn_blocking(move || {
+        VideoPreviewService::generate_sprite(&filepath, duration).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+    .map_err(|_| AppError::InternalServerError("Failed to generate sprite".into()))
+}
+
+async fn video_preview_source(
+    state: &AppState,
+    user: &UserModel,
+    id: i32,
+) -> Result<(String, Option<f64>), AppError> {
+    let media = MediaService::check_media_access(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    if media.media_type != MediaTypeEnum::Video {
+        return Err(AppError::BadRequest("Media is not a video".into()));
+    }
+
+    let duration = MediaMetadataService::get_metadata_for_media(&state.db, media.id)
+        .await
+        .ok()
+        .and_then(|metadata| metadata.duration);
+
+    Ok((media.filepath, duration))
+}
+
+pub async fn stream_hls_master(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let media = MediaService::check_media_access(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    if media.media_type != MediaTypeEnum::Video {
+        return Err(AppError::BadRequest("Media is not a video".into()));
+    }
+
+    let stream = HlsService::find_stream(&state.db, media.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    match stream {
+        Some(stream) if stream.status == StreamStatusEnum::Ready => {
+            let directory = stream
+                .directory
+                .ok_or_else(|| AppError::NotFound("Stream not found".into()))?;
+
+            DownloadService::stream_file(
+                &format!("{}/{}", directory, HLS_MASTER_PLAYLIST),
+                HlsService::content_type(HLS_MASTER_PLAYLIST),
+                &method,
+                &headers,
+            )
+            .await
+        }
+        _ => {
+            // Videos uploaded before HLS existed, or whose transcode failed, are
+            // queued on first playback; the client falls back to `stream` meanwhile.
+            HlsService::request_stream(&state.db, &media, &user).await?;
+
+            Ok(
+                ErrorResponseDto::new(StatusCode::ACCEPTED, "Stream is being prepared")
+                    .into_response(),
+            )
+        }
+    }
+}
+
+pub async fn stream_hls_file(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path((id, variant, file)): Path<(i32, String, String)>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let media = MediaService::check_media_access(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    let stream = HlsService::find_stream(&state.db, media.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .filter(|stream| stream.status == StreamStatusEnum::Ready)
+        .ok_or_else(|| AppError::NotFound("Stream not found".into()))?;
+
+    let filepath = HlsService::variant_file(&stream, &variant, &file)
+        .ok_or_else(|| AppError::NotFound("File not found".into()))?;
+
+    DownloadService::stream_file(
+        &filepath,
+        HlsService::content_type(&file),
+        &method,
+        &headers,
+    )
+    .await
+}
+
 pub async fn get_media_list(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<MediaListPayloadDto>,
@@ -138,10 +437,36 @@ pub async fn get_media_list(
     }
 }
 
+pub async fn bulk_update_media(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Json(payload): Json<MediaBulkUpdatePayloadDto>,
+) -> Result<Json<MediaBulkUpdateResponseDto>, AppError> {
+    if payload
+        .rating
+        .is_some_and(|rating| !(0..=5).contains(&rating))
+    {
+        return Err(AppError::BadRequest(
+            "Rating must be between 0 and 5".into(),
+        ));
+    }
+
+    let affected = MediaService::bulk_update_media(&state.db, user.id, &payload)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(MediaBulkUpdateResponseDto { affected }))
+}
+
 pub async fn get_media_detail(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
     Path(id): Path<i32>,
 ) -> Result<Json<MediaDetailResponseDto>, AppError> {
+    MediaService::check_media_access(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?;
+
     let media = MediaService::media_detail(&state.db, id)
         .await
         .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?;
@@ -150,5 +475,165 @@ pub async fn get_media_detail(
         .await
         .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?;
 
-    Ok(Json((media, metadata).into()))
+    let overrides = MediaMetadataOverrideService::get_overrides_for_media(&state.db, media.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?;
+
+    let tags = TagService::tags_for_media(&state.db, media.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?;
+
+    Ok(Json((media, metadata, overrides, tags).into()))
+}
+
+pub async fn update_media_metadata(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    Json(payload): Json<MediaMetadataUpdatePayloadDto>,
+) -> Result<Json<MediaMetadataOverrideModel>, AppError> {
+    let media = MediaService::check_media_access(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::NotFound("Media not found".into()))?;
+
+    if let Some(Some(latitude)) = payload.latitude {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(AppError::BadRequest(
+                "Latitude must be between -90 and 90".into(),
+            ));
+        }
+    }
+
+    if let Some(Some(longitude)) = payload.longitude {
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(AppError::BadRequest(
+                "Longitude must be between -180 and 180".into(),
+            ));
+        }
+    }
+
+    let overrides = MediaMetadataOverrideService::update_overrides(&state.db, media.id, payload)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(overrides))
+}
+
+pub async fn get_media_metadata_history(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<MediaMetadataAuditModel>>, AppError> {
+    let media = MediaService::check_media_access(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::NotFound("Media not found".into()))?;
+
+    let history = MediaMetadataOverrideService::get_audit_for_media(&state.db, media.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(history))
+}
+
+pub async fn reextract_metadata(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Json(payload): Json<ReextractMetadataPayloadDto>,
+) -> Result<Json<JobModel>, AppError> {
+    let job = JobService::create_job(
+        &state.db,
+        &user,
+        JobTypeEnum::ReextractMetadata,
+        serde_json::to_value(&payload).ok(),
+    )
+    .await
+    .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    let pool = state.db.clone();
+    let job_id = job.id;
+    let user_id = user.id;
+
+    JobService::spawn(state.db.clone(), user, &job, async move {
+        ReextractService::reextract_metadata(&pool, job_id, user_id, payload.media_ids).await
+    });
+
+    Ok(Json(job))
+}
+
+/// Admin-only: re-extracts the given media, or every media on the server, whoever owns it.
+pub async fn reextract_server_metadata(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Json(payload): Json<ReextractMetadataPayloadDto>,
+) -> Result<Json<JobModel>, AppError> {
+    let job = JobService::create_job(
+        &state.db,
+        &user,
+        JobTypeEnum::ReextractMetadata,
+        serde_json::to_value(&payload).ok(),
+    )
+    .await
+    .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    let pool = state.db.clone();
+    let job_id = job.id;
+
+    JobService::spawn(state.db.clone(), user, &job, async move {
+        ReextractService::reextract_server_metadata(&pool, job_id, payload.media_ids).await
+    });
+
+    Ok(Json(job))
+}
+
+pub async fn download_archive(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Json(payload): Json<MediaArchivePayloadDto>,
+) -> Result<Response, AppError> {
+    if payload.media_ids.is_none()
+        && payload.tag_id.is_none()
+        && payload.from.is_none()
+        && payload.to.is_none()
+    {
+        return Err(AppError::BadRequest(
+            "Select media, a tag or a date range to download".into(),
+        ));
+    }
+
+    let entries = MediaService::list_archive_media(&state.db, user.id, &payload)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    if entries.is_empty() {
+        return Err(AppError::NotFound("No media found".into()));
+    }
+
+    let filename = format!("photos-{}.zip", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+
+    DownloadService::download_archive(entries, &filename)
+}
+
+pub async fn get_duplicate_clusters(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Query(query): Query<DuplicateQueryDto>,
+) -> Result<Json<DuplicateClusterResponseDto>, AppError> {
+    let threshold = query.threshold.unwrap_or(6).min(16);
+
+    let clusters = DuplicateService::find_duplicates(&state.db, user.id, threshold)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(clusters))
+}
+
+/// Without an explicit variant, HEIF and RAW responses depend on the `Accept` header.
+fn vary_on_accept(mut response: Response, query: &MediaStreamQueryDto) -> Response {
+    if query.variant.is_none() {
+        response
+            .headers_mut()
+            .insert(VARY, HeaderValue::from_static("accept"));
+    }
+
+    response
 }
@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MediaMetadataAuditModel {
+    pub id: i32,
+
+    pub media_id: i32,
+    pub field: String,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+}
@@ -14,12 +14,22 @@ pub struct MediaMetadataModel {
     pub size: Option<i64>,
     pub width: Option<i32>,
     pub height: Option<i32>,
+    pub display_width: Option<i32>,
+    pub display_height: Option<i32>,
     pub hash: Option<String>,
     pub camera_make: Option<String>,
     pub camera_model: Option<String>,
     pub focal_length: Option<String>,
     pub aperture: Option<String>,
+    pub lens_model: Option<String>,
+    pub iso: Option<i32>,
+    pub exposure_time: Option<String>,
+    pub orientation: Option<i16>,
+    pub flash: Option<String>,
+    pub white_balance: Option<String>,
+    pub software: Option<String>,
     pub taken_at: Option<NaiveDateTime>,
+    pub taken_at_offset: Option<String>,
     pub duration: Option<f64>,
     pub frame_rate: Option<f32>,
     pub video_codec: Option<String>,
@@ -27,6 +37,17 @@ pub struct MediaMetadataModel {
     pub video_bitrate: Option<String>,
     pub audio_bitrate: Option<String>,
     pub sample_rate: Option<String>,
+    pub page_count: Option<i32>,
+    pub perceptual_hash: Option<String>,
+    pub blurhash: Option<String>,
+    pub exif: Option<serde_json::Value>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub rating: Option<i16>,
+    pub regions: Option<serde_json::Value>,
+    pub content_identifier: Option<String>,
+    pub motion_video_length: Option<i64>,
 
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -0,0 +1,25 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, FromRow)]
+pub struct MediaMetadataOverrideModel {
+    pub id: i32,
+    pub uuid: Uuid,
+
+    pub media_id: i32,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub taken_at: Option<NaiveDateTime>,
+    pub taken_at_offset: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub location_name: Option<String>,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
@@ -5,7 +5,7 @@ use uuid::Uuid;
 
 use crate::media::enums::media_type_enum::MediaTypeEnum;
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct MediaModel {
     pub id: i32,
     pub uuid: Uuid,
@@ -14,6 +14,11 @@ pub struct MediaModel {
     pub filename: String,
     pub filepath: String,
     pub media_type: MediaTypeEnum,
+    pub favorite: bool,
+    pub rating: i16,
+    pub archived: bool,
+    pub stack_id: Option<i32>,
+    pub motion_media_id: Option<i32>,
 
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::media::enums::stream_status_enum::StreamStatusEnum;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MediaStreamModel {
+    pub id: i32,
+    pub uuid: Uuid,
+
+    pub media_id: i32,
+    pub status: StreamStatusEnum,
+    pub directory: Option<String>,
+    pub variants: Option<serde_json::Value>,
+    pub error: Option<String>,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
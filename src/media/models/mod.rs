@@ -1,5 +1,15 @@
+pub mod media_metadata_audit_model;
 pub mod media_metadata_model;
+pub mod media_metadata_override_model;
 pub mod media_model;
+pub mod media_rendition_model;
+pub mod media_stream_model;
+pub mod rendition_preset_model;
 
+pub use media_metadata_audit_model::*;
 pub use media_metadata_model::*;
+pub use media_metadata_override_model::*;
 pub use media_model::*;
+pub use media_rendition_model::*;
+pub use media_stream_model::*;
+pub use rendition_preset_model::*;
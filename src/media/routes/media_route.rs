@@ -1,24 +1,49 @@
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
 };
 use std::sync::Arc;
 
 use crate::app::AppState;
-use crate::auth::middlewares::auth_middleware;
+use crate::auth::middlewares::{admin_middleware, auth_middleware};
 use crate::media::handlers::{
-    download_chunk, get_media_detail, get_media_list, get_thumbnail, stream_media, upload_chunk,
+    bulk_update_media, download_archive, download_chunk, get_duplicate_clusters, get_image,
+    get_media_detail, get_media_list, get_media_metadata_history, get_thumbnail,
+    reextract_metadata, reextract_server_metadata, stream_hls_file, stream_hls_master,
+    stream_media, stream_motion, stream_poster, stream_sprite, stream_sprite_track,
+    update_media_metadata, upload_chunk,
 };
 
 pub fn media_routes(app_state: Arc<AppState>) -> Router {
+    let admin_routes = Router::new()
+        .route("/admin/media/reextract", post(reextract_server_metadata))
+        .layer(middleware::from_fn(admin_middleware));
+
     Router::new()
         .route("/media", post(upload_chunk))
         .route("/media/list", post(get_media_list))
+        .route("/media/bulk-update", post(bulk_update_media))
+        .route("/media/reextract", post(reextract_metadata))
+        .route("/media/archive", post(download_archive))
+        .route("/media/duplicates", get(get_duplicate_clusters))
         .route("/media/{id}/download", post(download_chunk))
         .route("/media/{id}/thumbnail", get(get_thumbnail))
+        .route("/media/{id}/image", get(get_image))
         .route("/media/{id}/stream", get(stream_media))
+        .route("/media/{id}/motion", get(stream_motion))
+        .route("/media/{id}/poster", get(stream_poster))
+        .route("/media/{id}/sprite.jpg", get(stream_sprite))
+        .route("/media/{id}/sprite.vtt", get(stream_sprite_track))
+        .route("/media/{id}/hls/master.m3u8", get(stream_hls_master))
+        .route("/media/{id}/hls/{variant}/{file}", get(stream_hls_file))
+        .route("/media/{id}/metadata", patch(update_media_metadata))
+        .route(
+            "/media/{id}/metadata/history",
+            get(get_media_metadata_history),
+        )
         .route("/media/{id}", get(get_media_detail))
+        .merge(admin_routes)
         .layer(middleware::from_fn_with_state(
             app_state.clone(),
             auth_middleware,
@@ -0,0 +1,565 @@
+use axum::body::Body;
+use chrono::{Datelike, NaiveDateTime, Timelike, Utc};
+use crc32fast::Hasher;
+use std::collections::HashMap;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+};
+use tokio_util::io::ReaderStream;
+
+use crate::media::{dtos::MediaArchiveEntryDto, services::FileService};
+
+const LOCAL_FILE_HEADER: u32 = 0x04034b50;
+const DATA_DESCRIPTOR: u32 = 0x08074b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x02014b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x06064b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR: u32 = 0x07064b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+
+/// Bit 3: CRC follows the data in a descriptor. Bit 11: names are UTF-8.
+const FLAGS: u16 = 0x0008 | 0x0800;
+const VERSION_ZIP64: u16 = 45;
+const VERSION_DEFAULT: u16 = 20;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Sizes and offsets from here on no longer fit the classic 32-bit fields.
+const ZIP64_THRESHOLD: u64 = u32::MAX as u64;
+
+/// Where each entry ended up, for the central directory.
+struct ArchivedEntry {
+    name: String,
+    size: u64,
+    crc: u32,
+    offset: u64,
+    time: u16,
+    date: u16,
+}
+
+/// Writes a ZIP entry by entry to any async writer, ready to be streamed or saved.
+pub struct ArchiveWriter<W> {
+    writer: W,
+    archived: Vec<ArchivedEntry>,
+    offset: u64,
+    zip64_threshold: u64,
+}
+
+impl<W: AsyncWrite + Unpin> ArchiveWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            archived: Vec::new(),
+            offset: 0,
+            zip64_threshold: ZIP64_THRESHOLD,
+        }
+    }
+
+    /// Switches to Zip64 records at `threshold` bytes, so tests need not write 4 GiB.
+    #[cfg(test)]
+    fn with_zip64_threshold(writer: W, threshold: u64) -> Self {
+        Self {
+            zip64_threshold: threshold,
+            ..Self::new(writer)
+        }
+    }
+
+    /// Bytes written so far, which is what the finished archive weighs before its
+    /// central directory.
+    pub fn len(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.archived.is_empty()
+    }
+
+    pub async fn add_file(
+        &mut self,
+        name: &str,
+        file: &mut File,
+        modified: NaiveDateTime,
+    ) -> std::io::Result<()> {
+        let size = file.metadata().await?.len();
+
+        self.add_entry(name, size, file, modified).await
+    }
+
+    pub async fn add_bytes(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        modified: NaiveDateTime,
+    ) -> std::io::Result<()> {
+        let mut reader = data;
+
+        self.add_entry(name, data.len() as u64, &mut reader, modified)
+            .await
+    }
+
+    /// Copies exactly `size` bytes from `reader`, failing if it runs out sooner.
+    async fn add_entry<R: AsyncRead + Unpin>(
+        &mut self,
+        name: &str,
+        size: u64,
+        reader: &mut R,
+        modified: NaiveDateTime,
+    ) -> std::io::Result<()> {
+        let (time, date) = ArchiveService::dos_datetime(modified);
+
+        let header =
+            ArchiveService::local_file_header(name, size, time, date, self.zip64_threshold);
+        self.writer.write_all(&header).await?;
+
+        let mut hasher = Hasher::new();
+        let mut buffer = vec![0u8; BUFFER_SIZE.min(size as usize).max(1)];
+        let mut written = 0u64;
+
+        while written < size {
+            let limit = buffer.len().min((size - written) as usize);
+            let read = reader.read(&mut buffer[..limit]).await?;
+
+            if read == 0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("{} shrank while archiving", name),
+                ));
+            }
+
+            hasher.update(&buffer[..read]);
+            self.writer.write_all(&buffer[..read]).await?;
+            written += read as u64;
+        }
+
+        let crc = hasher.finalize();
+        let descriptor = ArchiveService::data_descriptor(crc, size, self.zip64_threshold);
+        self.writer.write_all(&descriptor).await?;
+
+        self.archived.push(ArchivedEntry {
+            name: name.to_string(),
+            size,
+            crc,
+            offset: self.offset,
+            time,
+            date,
+        });
+
+        self.offset += header.len() as u64 + size + descriptor.len() as u64;
+
+        Ok(())
+    }
+
+    /// Writes the central directory and flushes everything to the underlying writer.
+    pub async fn finish(mut self) -> std::io::Result<()> {
+        let mut central_directory = Vec::new();
+
+        for entry in &self.archived {
+            central_directory.extend(ArchiveService::central_directory_header(
+                entry,
+                self.zip64_threshold,
+            ));
+        }
+
+        self.writer.write_all(&central_directory).await?;
+
+        let end = ArchiveService::end_of_central_directory(
+            self.archived.len() as u64,
+            central_directory.len() as u64,
+            self.offset,
+            self.zip64_threshold,
+        );
+        self.writer.write_all(&end).await?;
+
+        self.writer.shutdown().await
+    }
+}
+
+pub struct ArchiveService {}
+
+impl ArchiveService {
+    /// Streams a ZIP of `entries` as it is written. Photos and videos are already
+    /// compressed, so entries are stored as they are; that keeps the server from doing
+    /// work that saves nothing and lets sizes be known before each entry starts.
+    pub fn stream_archive(entries: Vec<MediaArchiveEntryDto>) -> Body {
+        let (writer, reader) = tokio::io::duplex(BUFFER_SIZE);
+
+        tokio::spawn(async move {
+            if let Err(e) = Self::write_archive(writer, &entries).await {
+                // The client sees a truncated download, which archive tools reject.
+                eprintln!("Failed to stream archive: {:?}", e);
+            }
+        });
+
+        Body::from_stream(ReaderStream::with_capacity(reader, BUFFER_SIZE))
+    }
+
+    pub async fn write_archive<W: AsyncWrite + Unpin>(
+        writer: W,
+        entries: &[MediaArchiveEntryDto],
+    ) -> std::io::Result<()> {
+        let mut archive = ArchiveWriter::new(writer);
+        let mut names = HashMap::new();
+
+        for entry in entries {
+            let mut file = match File::open(&entry.filepath).await {
+                Ok(file) => file,
+                Err(e) => {
+                    eprintln!("Skipping media {} in archive: {}", entry.id, e);
+                    continue;
+                }
+            };
+
+            let name = Self::unique_name(
+                entry
+                    .original_filename
+                    .as_deref()
+                    .unwrap_or(&entry.filename),
+                &mut names,
+            );
+            let modified = entry.taken_at.unwrap_or_else(|| Utc::now().naive_utc());
+
+            archive.add_file(&name, &mut file, modified).await?;
+        }
+
+        archive.finish().await
+    }
+
+    /// Sizes are known up front and written here as well as in the descriptor, so
+    /// readers that never look at the central directory can still find the data.
+    fn local_file_header(name: &str, size: u64, time: u16, date: u16, threshold: u64) -> Vec<u8> {
+        let zip64 = size >= threshold;
+        let version = match zip64 {
+            true => VERSION_ZIP64,
+            false => VERSION_DEFAULT,
+        };
+        let mut header = Vec::with_capacity(30 + name.len() + 20);
+
+        put_u32(&mut header, LOCAL_FILE_HEADER);
+        put_u16(&mut header, version);
+        put_u16(&mut header, FLAGS);
+        put_u16(&mut header, 0);
+        put_u16(&mut header, time);
+        put_u16(&mut header, date);
+        put_u32(&mut header, 0);
+        put_u32(&mut header, Self::clamp(size, threshold));
+        put_u32(&mut header, Self::clamp(size, threshold));
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, if zip64 { 20 } else { 0 });
+        header.extend_from_slice(name.as_bytes());
+
+        if zip64 {
+            put_u16(&mut header, ZIP64_EXTRA_FIELD);
+            put_u16(&mut header, 16);
+            put_u64(&mut header, size);
+            put_u64(&mut header, size);
+        }
+
+        header
+    }
+
+    fn data_descriptor(crc: u32, size: u64, threshold: u64) -> Vec<u8> {
+        let mut descriptor = Vec::with_capacity(24);
+
+        put_u32(&mut descriptor, DATA_DESCRIPTOR);
+        put_u32(&mut descriptor, crc);
+
+        match size >= threshold {
+            true => {
+                put_u64(&mut descriptor, size);
+                put_u64(&mut descriptor, size);
+            }
+            false => {
+                put_u32(&mut descriptor, size as u32);
+                put_u32(&mut descriptor, size as u32);
+            }
+        }
+
+        descriptor
+    }
+
+    fn central_directory_header(entry: &ArchivedEntry, threshold: u64) -> Vec<u8> {
+        let large_size = entry.size >= threshold;
+        let large_offset = entry.offset >= threshold;
+
+        let mut extra = Vec::new();
+        if large_size {
+            put_u64(&mut extra, entry.size);
+            put_u64(&mut extra, entry.size);
+        }
+        if large_offset {
+            put_u64(&mut extra, entry.offset);
+        }
+
+        let version = match large_size || large_offset {
+            true => VERSION_ZIP64,
+            false => VERSION_DEFAULT,
+        };
+        let extra_len = match extra.is_empty() {
+            true => 0,
+            false => 4 + extra.len(),
+        };
+
+        let mut header = Vec::with_capacity(46 + entry.name.len() + extra_len);
+
+        put_u32(&mut header, CENTRAL_DIRECTORY_HEADER);
+        put_u16(&mut header, VERSION_ZIP64);
+        put_u16(&mut header, version);
+        put_u16(&mut header, FLAGS);
+        put_u16(&mut header, 0);
+        put_u16(&mut header, entry.time);
+        put_u16(&mut header, entry.date);
+        put_u32(&mut header, entry.crc);
+        put_u32(&mut header, Self::clamp(entry.size, threshold));
+        put_u32(&mut header, Self::clamp(entry.size, threshold));
+        put_u16(&mut header, entry.name.len() as u16);
+        put_u16(&mut header, extra_len as u16);
+        put_u16(&mut header, 0);
+        put_u16(&mut header, 0);
+        put_u16(&mut header, 0);
+        put_u32(&mut header, 0);
+        put_u32(&mut header, Self::clamp(entry.offset, threshold));
+        header.extend_from_slice(entry.name.as_bytes());
+
+        if !extra.is_empty() {
+            put_u16(&mut header, ZIP64_EXTRA_FIELD);
+            put_u16(&mut header, extra.len() as u16);
+            header.extend(extra);
+        }
+
+        header
+    }
+
+    /// The Zip64 record and its locator precede the classic record only when a count,
+    /// size or offset no longer fits it.
+    fn end_of_central_directory(count: u64, size: u64, offset: u64, threshold: u64) -> Vec<u8> {
+        let mut end = Vec::with_capacity(98);
+
+        if count >= u16::MAX as u64 || size >= threshold || offset >= threshold {
+            let record_offset = offset + size;
+
+            put_u32(&mut end, ZIP64_END_OF_CENTRAL_DIRECTORY);
+            put_u64(&mut end, 44);
+            put_u16(&mut end, VERSION_ZIP64);
+            put_u16(&mut end, VERSION_ZIP64);
+            put_u32(&mut end, 0);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, count);
+            put_u64(&mut end, count);
+            put_u64(&mut end, size);
+            put_u64(&mut end, offset);
+
+            put_u32(&mut end, ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, record_offset);
+            put_u32(&mut end, 1);
+        }
+
+        put_u32(&mut end, END_OF_CENTRAL_DIRECTORY);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, count.min(u16::MAX as u64) as u16);
+        put_u16(&mut end, count.min(u16::MAX as u64) as u16);
+        put_u32(&mut end, Self::clamp(size, threshold));
+        put_u32(&mut end, Self::clamp(offset, threshold));
+        put_u16(&mut end, 0);
+
+        end
+    }
+
+    /// `IMG_0001.JPG` becomes `IMG_0001 (1).JPG` when the name is already taken,
+    /// compared case-insensitively since that is how most desktops will extract it.
+    /// `names` maps each name in the archive to the next counter to try for it.
+    pub fn unique_name(filename: &str, names: &mut HashMap<String, u32>) -> String {
+        let filename = FileService::clean_filename(filename);
+        let key = filename.to_lowercase();
+
+        let Some(&next) = names.get(&key) else {
+            names.insert(key, 1);
+            return filename;
+        };
+
+        let (stem, extension) = match filename.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (filename.as_str(), String::new()),
+        };
+
+        let mut counter = next;
+        let mut name = format!("{} ({}){}", stem, counter, extension);
+
+        while names.contains_key(&name.to_lowercase()) {
+            counter += 1;
+            name = format!("{} ({}){}", stem, counter, extension);
+        }
+
+        names.insert(key, counter + 1);
+        names.insert(name.to_lowercase(), 1);
+
+        name
+    }
+
+    /// MS-DOS timestamps start in 1980 and count seconds in twos.
+    pub fn dos_datetime(datetime: NaiveDateTime) -> (u16, u16) {
+        if datetime.year() < 1980 {
+            return (0, (1 << 5) | 1);
+        }
+
+        let time = (datetime.hour() << 11) | (datetime.minute() << 5) | (datetime.second() / 2);
+        let date = ((datetime.year() as u32 - 1980).min(127) << 9)
+            | (datetime.month() << 5)
+            | datetime.day();
+
+        (time as u16, date as u16)
+    }
+
+    /// Values at or past the Zip64 threshold are left to the Zip64 fields, and the
+    /// classic field is set to all ones to say so.
+    fn clamp(value: u64, threshold: u64) -> u32 {
+        match value >= threshold {
+            true => u32::MAX,
+            false => value as u32,
+        }
+    }
+}
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    fn taken_at() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 17)
+            .unwrap()
+            .and_hms_opt(14, 30, 12)
+            .unwrap()
+    }
+
+    fn read_back(bytes: Vec<u8>) -> Vec<(String, Vec<u8>)> {
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+
+        (0..archive.len())
+            .map(|index| {
+                let mut file = archive.by_index(index).unwrap();
+                let mut data = Vec::new();
+                file.read_to_end(&mut data).unwrap();
+
+                (file.name().to_string(), data)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn archive_reads_back_with_unique_names() {
+        let dir = std::env::temp_dir().join(format!("archive-service-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let files: [(&str, &[u8]); 4] = [
+            ("IMG_0001.JPG", b"first"),
+            ("img_0001.jpg", b"second"),
+            ("Été à Zürich 写真.jpg", b"unicode"),
+            ("empty.txt", b""),
+        ];
+
+        let entries: Vec<MediaArchiveEntryDto> = files
+            .iter()
+            .enumerate()
+            .map(|(index, (name, data))| {
+                let filepath = dir.join(format!("{}.bin", index));
+                std::fs::write(&filepath, data).unwrap();
+
+                MediaArchiveEntryDto {
+                    id: index as i32,
+                    filename: format!("{}.bin", index),
+                    filepath: filepath.to_string_lossy().into_owned(),
+                    original_filename: Some(name.to_string()),
+                    taken_at: Some(taken_at()),
+                }
+            })
+            .collect();
+
+        let mut bytes = Vec::new();
+        ArchiveService::write_archive(&mut bytes, &entries)
+            .await
+            .unwrap();
+
+        let archived = read_back(bytes);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let expected = [
+            ("IMG_0001.JPG", &b"first"[..]),
+            ("img_0001 (1).jpg", b"second"),
+            ("Été à Zürich 写真.jpg", b"unicode"),
+            ("empty.txt", b""),
+        ];
+
+        assert_eq!(archived.len(), expected.len());
+
+        for ((name, data), (expected_name, expected_data)) in archived.iter().zip(expected) {
+            assert_eq!(name, expected_name);
+            assert_eq!(data, expected_data);
+        }
+    }
+
+    #[tokio::test]
+    async fn zip64_archive_reads_back() {
+        let mut bytes = Vec::new();
+        let mut archive = ArchiveWriter::with_zip64_threshold(&mut bytes, 8);
+
+        archive
+            .add_bytes("small.txt", b"tiny", taken_at())
+            .await
+            .unwrap();
+        archive
+            .add_bytes("large.jpg", b"larger than the threshold", taken_at())
+            .await
+            .unwrap();
+        archive.finish().await.unwrap();
+
+        assert_eq!(
+            &bytes[bytes.len() - 98..bytes.len() - 94],
+            ZIP64_END_OF_CENTRAL_DIRECTORY.to_le_bytes()
+        );
+
+        let archived = read_back(bytes);
+
+        assert_eq!(
+            archived,
+            vec![
+                ("small.txt".to_string(), b"tiny".to_vec()),
+                (
+                    "large.jpg".to_string(),
+                    b"larger than the threshold".to_vec()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_name_numbers_collisions_case_insensitively() {
+        let mut names = HashMap::new();
+
+        let named: Vec<String> = ["a.jpg", "A.JPG", "a (1).jpg", "a.jpg", "README"]
+            .iter()
+            .map(|name| ArchiveService::unique_name(name, &mut names))
+            .collect();
+
+        assert_eq!(
+            named,
+            ["a.jpg", "A (1).JPG", "a (1) (1).jpg", "a (2).jpg", "README"]
+        );
+    }
+}
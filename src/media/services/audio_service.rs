@@ -0,0 +1,86 @@
+use ffprobe::ffprobe;
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::media::{models::MediaMetadataModel, services::VideoService};
+
+pub struct AudioService {}
+
+impl AudioService {
+    pub fn extract_audio_metadata(path: &str, metadata: &mut MediaMetadataModel) {
+        match ffprobe(path) {
+            Ok(info) => {
+                if let Some(duration_str) = &info.format.duration {
+                    if let Ok(duration) = duration_str.parse::<f64>() {
+                        metadata.duration = Some(duration);
+                    }
+                }
+
+                if let Some(tags) = &info.format.tags {
+                    if let Some(creation_time) = &tags.creation_time {
+                        if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(creation_time) {
+                            metadata.taken_at = Some(datetime.naive_utc());
+                        }
+                    }
+                }
+
+                // Cover art shows up as a video stream; only the audio one matters here.
+                if let Some(stream) = info
+                    .streams
+                    .iter()
+                    .find(|s| s.codec_type.as_deref() == Some("audio"))
+                {
+                    metadata.audio_codec = stream.codec_name.clone();
+                    metadata.audio_bitrate = stream
+                        .bit_rate
+                        .clone()
+                        .or_else(|| info.format.bit_rate.clone());
+                    metadata.sample_rate = stream.sample_rate.clone();
+                }
+            }
+            Err(e) => {
+                eprintln!("Could not analyze file with ffprobe: {:?}", e);
+            }
+        }
+    }
+
+    /// Renders the embedded cover art, if the file carries one.
+    pub async fn generate_audio_thumbnail(
+        filepath: &str,
+        thumbnail_path: &str,
+        max_size: u32,
+        square: bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(parent) = Path::new(thumbnail_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let status = Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-hide_banner",
+                "-loglevel",
+                "error",
+                "-i",
+                filepath,
+                "-an",
+                "-frames:v",
+                "1",
+                "-vf",
+                &VideoService::thumbnail_scale_filter(max_size, square),
+                thumbnail_path,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            return Err("No cover art found".into());
+        }
+
+        Ok(thumbnail_path.to_string())
+    }
+}
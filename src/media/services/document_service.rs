@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    process::{Command, Stdio},
+};
+
+use crate::media::{models::MediaMetadataModel, services::PhotoService};
+
+pub struct DocumentService {}
+
+impl DocumentService {
+    pub fn is_pdf(mime_type: &str) -> bool {
+        mime_type == "application/pdf"
+    }
+
+    pub fn extract_document_metadata(
+        path: &str,
+        mime_type: &str,
+        metadata: &mut MediaMetadataModel,
+    ) {
+        if !Self::is_pdf(mime_type) {
+            return;
+        }
+
+        metadata.page_count = Self::pdf_page_count(path);
+    }
+
+    /// Renders the first page of a PDF. Other documents fall back to the icon.
+    pub async fn generate_document_thumbnail(
+        filepath: &str,
+        thumbnail_path: &str,
+        max_size: u32,
+        square: bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let page = Self::render_first_page(filepath, max_size)?;
+        let img = image::load_from_memory(&page)?;
+
+        let thumbnail = PhotoService::fit_thumbnail(img, max_size, square);
+
+        PhotoService::save_thumbnail(&thumbnail, thumbnail_path)?;
+
+        Ok(thumbnail_path.to_string())
+    }
+
+    /// Decodes the first page as a PNG scaled to fit `max_size`.
+    pub fn render_first_page(
+        filepath: &str,
+        max_size: u32,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let output = Command::new("pdftoppm")
+            .args([
+                "-f",
+                "1",
+                "-l",
+                "1",
+                "-singlefile",
+                "-png",
+                "-scale-to",
+                &max_size.to_string(),
+                filepath,
+            ])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err("Failed to render PDF page".into());
+        }
+
+        Ok(output.stdout)
+    }
+
+    /// Asks `pdfinfo` first; without poppler, counts the page objects in the file,
+    /// which works for PDFs that do not compress their object streams.
+    fn pdf_page_count(path: &str) -> Option<i32> {
+        let pdfinfo = Command::new("pdfinfo")
+            .arg(path)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Pages:"))
+                    .and_then(|pages| pages.trim().parse::<i32>().ok())
+            });
+
+        if pdfinfo.is_some() {
+            return pdfinfo;
+        }
+
+        let data = fs::read(path).ok()?;
+
+        let count = [b"/Type /Page".as_slice(), b"/Type/Page".as_slice()]
+            .iter()
+            .map(|needle| {
+                data.windows(needle.len() + 1)
+                    .filter(|w| w.starts_with(needle) && w[needle.len()] != b's')
+                    .count()
+            })
+            .sum::<usize>();
+
+        Some(count as i32).filter(|count| *count > 0)
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{body::Body, response::Response};
-use hyper::{HeaderMap, StatusCode};
-use std::path::PathBuf;
+use hyper::{HeaderMap, Method, StatusCode};
+use std::time::UNIX_EPOCH;
 use tokio::{
     fs::File,
     io::{AsyncReadExt, AsyncSeekExt, SeekFrom},
@@ -9,10 +9,15 @@ use tokio_util::io::ReaderStream;
 
 use crate::errors::app_error::AppError;
 use crate::media::{
-    dtos::MediaDownloadPayloadDto,
+    dtos::{MediaArchiveEntryDto, MediaDownloadPayloadDto},
     models::{MediaMetadataModel, MediaModel},
+    services::{ArchiveService, ByteRange, RangeService},
 };
 
+/// Thumbnails are addressed by content hash through their ETag, so a cached copy
+/// never needs revalidating within a session.
+const THUMBNAIL_CACHE_CONTROL: &str = "private, max-age=31536000, immutable";
+
 pub struct DownloadService {}
 
 impl DownloadService {
@@ -46,66 +51,105 @@ impl DownloadService {
     pub async fn stream_media(
         media: MediaModel,
         metadata: MediaMetadataModel,
-        headers: HeaderMap,
+        method: &Method,
+        headers: &HeaderMap,
     ) -> Result<Response, AppError> {
-        let path = PathBuf::from(media.filepath);
-
-        if !path.exists() {
-            return Err(AppError::NotFound("File not found".into()));
-        }
-
         let mime_type = metadata
             .mime_type
             .unwrap_or("application/octet-stream".to_string());
 
-        let file_size = match metadata.size {
-            Some(size) => size as u64,
-            None => 0,
+        Self::stream_file(&media.filepath, &mime_type, method, headers).await
+    }
+
+    /// Serves a file with byte-range and conditional request support: 200 for the whole
+    /// file, 206 for a single range, 416 for a range past the end and 304 when the
+    /// client's copy is current. HEAD gets the same headers without opening the file.
+    pub async fn stream_file(
+        filepath: &str,
+        mime_type: &str,
+        method: &Method,
+        headers: &HeaderMap,
+    ) -> Result<Response, AppError> {
+        let file_metadata = tokio::fs::metadata(filepath)
+            .await
+            .ok()
+            .filter(|m| m.is_file())
+            .ok_or_else(|| AppError::NotFound("File not found".into()))?;
+
+        let file_size = file_metadata.len();
+        let modified = file_metadata.modified().unwrap_or(UNIX_EPOCH);
+
+        let etag = RangeService::etag(file_size, modified);
+        let last_modified = RangeService::last_modified(modified);
+
+        let builder = Response::builder()
+            .header("Accept-Ranges", "bytes")
+            .header("ETag", &etag)
+            .header("Last-Modified", &last_modified);
+
+        if RangeService::not_modified(headers, &etag, modified) {
+            return builder
+                .status(StatusCode::NOT_MODIFIED)
+                .body(Body::empty())
+                .map_err(|_| AppError::InternalServerError("Failed to build response".into()));
+        }
+
+        let range = match headers.get("range").and_then(|h| h.to_str().ok()) {
+            Some(range) if RangeService::if_range_matches(headers, &etag, modified) => {
+                RangeService::parse_range(range, file_size)
+            }
+            _ => ByteRange::Full,
         };
 
-        let range_header = headers.get("range").and_then(|h| h.to_str().ok());
-
-        let (start, end) = if let Some(range) = range_header {
-            if let Some(range) = range.strip_prefix("bytes=") {
-                let parts: Vec<_> = range.split('-').collect();
-                let start = parts
-                    .first()
-                    .and_then(|s| s.parse::<u64>().ok())
-                    .unwrap_or(0);
-                let end = parts
-                    .get(1)
-                    .and_then(|e| e.parse::<u64>().ok())
-                    .unwrap_or(file_size - 1);
-                (start, end)
-            } else {
-                (0, file_size - 1)
+        let builder = builder.header("Content-Type", mime_type);
+
+        let (builder, start, length) = match range {
+            ByteRange::Full => (builder.status(StatusCode::OK), 0, file_size),
+            ByteRange::Partial(start, end) => (
+                builder.status(StatusCode::PARTIAL_CONTENT).header(
+                    "Content-Range",
+                    format!("bytes {}-{}/{}", start, end, file_size),
+                ),
+                start,
+                end - start + 1,
+            ),
+            ByteRange::Unsatisfiable => {
+                return builder
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header("Content-Range", format!("bytes */{}", file_size))
+                    .body(Body::empty())
+                    .map_err(|_| AppError::InternalServerError("Failed to build response".into()));
             }
-        } else {
-            (0, file_size - 1)
         };
-        let chunk_size = end - start + 1;
 
-        let mut file = tokio::fs::File::open(&path).await.unwrap();
-        file.seek(SeekFrom::Start(start)).await.unwrap();
+        let builder = builder.header("Content-Length", length.to_string());
 
-        let stream = ReaderStream::with_capacity(file.take(chunk_size), 8192);
+        if method == Method::HEAD {
+            return builder
+                .body(Body::empty())
+                .map_err(|_| AppError::InternalServerError("Failed to build response".into()));
+        }
 
-        let response = Response::builder()
-            .status(StatusCode::PARTIAL_CONTENT)
-            .header("Content-Type", mime_type)
-            .header("Content-Length", chunk_size.to_string())
-            .header("Accept-Ranges", "bytes")
-            .header(
-                "Content-Range",
-                format!("bytes {}-{}/{}", start, end, file_size),
-            )
-            .body(axum::body::Body::from_stream(stream))
-            .unwrap();
+        let mut file = File::open(filepath)
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to open file".into()))?;
 
-        Ok(response)
+        file.seek(SeekFrom::Start(start))
+            .await
+            .map_err(|_| AppError::InternalServerError("Failed to seek file".into()))?;
+
+        let stream = ReaderStream::with_capacity(file.take(length), 8192);
+
+        builder
+            .body(Body::from_stream(stream))
+            .map_err(|_| AppError::InternalServerError("Failed to build response".into()))
     }
 
-    pub async fn download_thumbnail(file_path: &str) -> Result<Response, AppError> {
+    pub async fn download_thumbnail(
+        file_path: &str,
+        content_type: &str,
+        etag: &str,
+    ) -> Result<Response, AppError> {
         let file = tokio::fs::File::open(&file_path)
             .await
             .map_err(|_| AppError::NotFound("File not found".into()))?;
@@ -113,10 +157,46 @@ impl DownloadService {
 
         let response = Response::builder()
             .status(StatusCode::OK)
-            .header("Content-Type", "image/jpeg")
+            .header("Content-Type", content_type)
+            .header("ETag", etag)
+            .header("Cache-Control", THUMBNAIL_CACHE_CONTROL)
             .body(axum::body::Body::from_stream(stream))
             .map_err(|_| AppError::InternalServerError("Failed to build response".into()))?;
 
         Ok(response)
     }
+
+    pub fn not_modified(etag: &str) -> Result<Response, AppError> {
+        Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header("ETag", etag)
+            .header("Cache-Control", THUMBNAIL_CACHE_CONTROL)
+            .body(Body::empty())
+            .map_err(|_| AppError::InternalServerError("Failed to build response".into()))
+    }
+
+    pub fn download_icon(icon: &'static [u8]) -> Result<Response, AppError> {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "image/svg+xml")
+            .body(Body::from(icon))
+            .map_err(|_| AppError::InternalServerError("Failed to build response".into()))
+    }
+
+    /// No Content-Length: the archive is written while it is sent.
+    pub fn download_archive(
+        entries: Vec<MediaArchiveEntryDto>,
+        filename: &str,
+    ) -> Result<Response, AppError> {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/zip")
+            .header(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", filename),
+            )
+            .header("Cache-Control", "no-store")
+            .body(ArchiveService::stream_archive(entries))
+            .map_err(|_| AppError::InternalServerError("Failed to build response".into()))
+    }
 }
@@ -0,0 +1,79 @@
+use sqlx::PgPool;
+
+use crate::media::{
+    dtos::{DuplicateClusterDto, DuplicateClusterResponseDto, DuplicateMediaRow},
+    services::PerceptualHashService,
+};
+
+pub struct DuplicateService {}
+
+impl DuplicateService {
+    pub async fn find_duplicates(
+        pool: &PgPool,
+        user_id: i32,
+        threshold: u32,
+    ) -> Result<DuplicateClusterResponseDto, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            DuplicateMediaRow,
+            r#"
+                select a.id, a.uuid, a.filename, a.media_type, b.original_filename, b.mime_type, b.size, b.width, b.height, b.camera_make, b.taken_at, b.perceptual_hash
+                from media a join media_metadata b on a.id = b.media_id
+                where a.deleted_at is null and b.deleted_at is null and a.user_id = $1 and b.perceptual_hash is not null
+                order by a.id
+            "#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let (rows, hashes): (Vec<DuplicateMediaRow>, Vec<u64>) = rows
+            .into_iter()
+            .filter_map(|row| {
+                let hash = row
+                    .perceptual_hash
+                    .as_deref()
+                    .and_then(PerceptualHashService::parse)?;
+
+                Some((row, hash))
+            })
+            .unzip();
+
+        let mut rows: Vec<Option<DuplicateMediaRow>> = rows.into_iter().map(Some).collect();
+
+        let clusters = PerceptualHashService::cluster(&hashes, threshold)
+            .into_iter()
+            .map(|indices| {
+                let media: Vec<DuplicateMediaRow> = indices
+                    .into_iter()
+                    .filter_map(|index| rows[index].take())
+                    .collect();
+
+                let suggested_media_id = Self::suggest_best(&media);
+
+                DuplicateClusterDto {
+                    suggested_media_id,
+                    media,
+                }
+            })
+            .collect();
+
+        Ok(DuplicateClusterResponseDto {
+            threshold,
+            clusters,
+        })
+    }
+
+    /// Prefers the most pixels, then the largest file (least re-compressed), then
+    /// an item that still carries camera EXIF, then the oldest upload.
+    fn suggest_best(media: &[DuplicateMediaRow]) -> i32 {
+        media
+            .iter()
+            .max_by_key(|m| {
+                let pixels = i64::from(m.width.unwrap_or(0)) * i64::from(m.height.unwrap_or(0));
+
+                (pixels, m.size.unwrap_or(0), m.camera_make.is_some(), -m.id)
+            })
+            .map(|m| m.id)
+            .unwrap_or_default()
+    }
+}
@@ -3,16 +3,31 @@ use sha2::{Digest, Sha256};
 use std::{
     fs::File,
     io::{BufReader, Read},
+    path::Path,
 };
 
 use crate::errors::app_error::AppError;
 
+/// Browser-viewable JPEG renditions of HEIF and RAW originals.
+pub const RENDITION_DIR: &str = "renditions";
+pub const SIDECAR_DIR: &str = "sidecars";
+/// Video halves extracted from Motion Photos.
+pub const MOTION_DIR: &str = "motion";
+/// Video poster frames.
+pub const POSTER_DIR: &str = "posters";
+/// Scrub preview sprite sheets and the WebVTT tracks describing them.
+pub const SPRITE_DIR: &str = "sprites";
+/// Browser-playable copies of videos.
+pub const PLAYBACK_DIR: &str = "playback";
+/// HLS playlists and segments, one directory per video.
+pub const HLS_DIR: &str = "hls";
+
 pub struct FileService {}
 
 impl FileService {
     pub fn generate_file_hash(path: &str) -> Result<String, AppError> {
         let file = File::open(path)
-            .map_err(|_| (AppError::InternalServerError("Failed to open file".into())))?;
+            .map_err(|_| AppError::InternalServerError("Failed to open file".into()))?;
 
         let mut bufreader = BufReader::new(file);
         let mut hasher = Sha256::new();
@@ -21,7 +36,7 @@ impl FileService {
         loop {
             let bytes_read = bufreader
                 .read(&mut buffer)
-                .map_err(|_| (AppError::InternalServerError("Failed to generate hash".into())))?;
+                .map_err(|_| AppError::InternalServerError("Failed to generate hash".into()))?;
 
             if bytes_read == 0 {
                 break;
@@ -36,6 +51,13 @@ impl FileService {
     }
 
     pub fn sanitize_filename(filename: &str) -> String {
+        let prefix = Self::generate_random_prefix(8);
+
+        format!("{}{}", prefix, Self::clean_filename(filename))
+    }
+
+    /// Replaces characters that are unsafe in paths, keeping the name recognisable.
+    pub fn clean_filename(filename: &str) -> String {
         let unsafe_chars = ['<', '>', ':', '"', '/', '\\', '|', '?', '*', '\0'];
 
         let mut sanitized: String = filename
@@ -55,9 +77,28 @@ impl FileService {
             sanitized = "default_filename".to_string();
         }
 
-        let prefix = Self::generate_random_prefix(8);
+        sanitized
+    }
+
+    /// Files derived from an original live beside it, one directory per kind, e.g.
+    /// `derived_path("./uploads/{uuid}/IMG.heic", RENDITION_DIR, "jpg")` is
+    /// `./uploads/{uuid}/renditions/IMG.jpg`. An empty `extension` names a directory.
+    pub fn derived_path(filepath: &str, dir: &str, extension: &str) -> Option<String> {
+        let path = Path::new(filepath);
+        let parent = path.parent()?.to_str()?;
+        let stem = path.file_stem()?.to_str()?;
+
+        match extension.is_empty() {
+            true => Some(format!("{}/{}/{}", parent, dir, stem)),
+            false => Some(format!("{}/{}/{}.{}", parent, dir, stem, extension)),
+        }
+    }
+
+    /// XMP sidecars are kept apart from media, e.g. `./uploads/{uuid}/sidecars/IMG_1234.xmp`.
+    pub fn sidecar_dir(filepath: &str) -> Option<String> {
+        let parent = Path::new(filepath).parent()?.to_str()?;
 
-        format!("{}{}", prefix, sanitized)
+        Some(format!("{}/{}", parent, SIDECAR_DIR))
     }
 
     fn generate_random_prefix(length: usize) -> String {
@@ -0,0 +1,91 @@
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::media::services::{FileService, RENDITION_DIR};
+
+pub struct HeifService {}
+
+impl HeifService {
+    pub fn is_heif(mime_type: &str) -> bool {
+        matches!(
+            mime_type,
+            "image/heif" | "image/heic" | "image/heif-sequence"
+        )
+    }
+
+    pub fn is_heif_file(filepath: &str) -> bool {
+        infer::get_from_path(filepath)
+            .ok()
+            .flatten()
+            .is_some_and(|t| Self::is_heif(t.mime_type()))
+    }
+
+    /// Only clients that list HEIC/HEIF explicitly get the original. Browsers send
+    /// `image/*,*/*` on every image request without being able to decode HEIC.
+    pub fn accepts_heif(accept: Option<&str>) -> bool {
+        accept.is_some_and(|accept| {
+            accept.split(',').any(|part| {
+                let media_range = part.split(';').next().unwrap_or_default().trim();
+
+                matches!(media_range, "image/heic" | "image/heif")
+            })
+        })
+    }
+
+    /// Returns the JPEG rendition for a HEIC/HEIF original, decoding it on first use.
+    pub fn generate_jpeg_rendition(filepath: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let rendition_path =
+            FileService::derived_path(filepath, RENDITION_DIR, "jpg").ok_or("Invalid filename")?;
+
+        if Path::new(&rendition_path).exists() {
+            return Ok(rendition_path);
+        }
+
+        if let Some(parent) = Path::new(&rendition_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if Self::convert_with_heif_convert(filepath, &rendition_path)
+            || Self::convert_with_ffmpeg(filepath, &rendition_path)
+        {
+            return Ok(rendition_path);
+        }
+
+        Err("Failed to decode HEIF image".into())
+    }
+
+    fn convert_with_heif_convert(filepath: &str, output_path: &str) -> bool {
+        Command::new("heif-convert")
+            .args(["-q", "90", filepath, output_path])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+            && Path::new(output_path).exists()
+    }
+
+    fn convert_with_ffmpeg(filepath: &str, output_path: &str) -> bool {
+        Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-hide_banner",
+                "-loglevel",
+                "error",
+                "-i",
+                filepath,
+                "-frames:v",
+                "1",
+                "-q:v",
+                "2",
+                output_path,
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+            && Path::new(output_path).exists()
+    }
+}
@@ -0,0 +1,433 @@
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::auth::services::AuthService;
+use crate::errors::app_error::AppError;
+use crate::media::{
+    enums::stream_status_enum::StreamStatusEnum,
+    models::{MediaMetadataModel, MediaModel, MediaStreamModel},
+    services::{FileService, MediaMetadataService, HLS_DIR, TRANSCODE_SLOTS},
+};
+use crate::user::models::UserModel;
+
+pub const HLS_MASTER_PLAYLIST: &str = "master.m3u8";
+pub const HLS_VARIANT_PLAYLIST: &str = "index.m3u8";
+
+/// Name, short side in pixels and video bitrate in kbit/s. The short side keeps
+/// portrait videos at the same quality as landscape ones.
+const HLS_LADDER: [(&str, u32, u32); 4] = [
+    ("1080p", 1080, 5000),
+    ("720p", 720, 2800),
+    ("480p", 480, 1400),
+    ("360p", 360, 800),
+];
+const HLS_AUDIO_BITRATE: u32 = 128;
+const HLS_SEGMENT_SECONDS: u32 = 6;
+
+pub struct HlsService {}
+
+impl HlsService {
+    pub async fn find_stream(
+        pool: &PgPool,
+        media_id: i32,
+    ) -> Result<Option<MediaStreamModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaStreamModel,
+            r#"select * from media_streams where deleted_at is null and media_id = $1"#,
+            media_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Retires a media's stream, e.g. once its original changed, so the next request
+    /// claims it again.
+    pub async fn delete_stream(pool: &PgPool, media_id: i32) -> Result<u64, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"update media_streams set deleted_at = $1, updated_at = $1, updated_by = $2 where deleted_at is null and media_id = $3"#,
+            now,
+            actor_id,
+            media_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Fails streams left pending or processing by a previous run, whose transcode
+    /// died with it, so the next request queues them again.
+    pub async fn fail_interrupted_streams(pool: &PgPool) -> Result<u64, sqlx::Error> {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+                update media_streams set status = $1, error = 'Interrupted by a server restart', updated_at = $2
+                where deleted_at is null and status in ($3, $4)
+            "#,
+            StreamStatusEnum::Failed as i32,
+            now,
+            StreamStatusEnum::Pending as i32,
+            StreamStatusEnum::Processing as i32,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Queues a transcode unless one is already pending, running or done. A failed
+    /// stream is retried.
+    pub async fn request_stream(
+        pool: &PgPool,
+        media: &MediaModel,
+        user: &UserModel,
+    ) -> Result<(), AppError> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let claimed = sqlx::query_as!(
+            MediaStreamModel,
+            r#"
+                insert into media_streams (media_id, status, created_at, updated_at, created_by, updated_by)
+                values ($1, $2, $3, $3, $4, $4)
+                on conflict (media_id) do update set
+                status = excluded.status, error = null, updated_at = excluded.updated_at,
+                updated_by = excluded.updated_by, deleted_at = null
+                where media_streams.status = $5 or media_streams.deleted_at is not null
+                returning *
+            "#,
+            media.id,
+            StreamStatusEnum::Pending as i32,
+            now,
+            actor_id,
+            StreamStatusEnum::Failed as i32,
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if claimed.is_none() {
+            return Ok(());
+        }
+
+        let pool = pool.clone();
+        let media_id = media.id;
+        let filepath = media.filepath.clone();
+
+        tokio::spawn(AuthService::login(user.clone(), async move {
+            let _slot = TRANSCODE_SLOTS.acquire().await;
+
+            if let Err(e) = Self::generate_stream(&pool, media_id, &filepath).await {
+                eprintln!(
+                    "Failed to generate HLS stream for media {}: {:?}",
+                    media_id, e
+                );
+
+                if let Err(e) = Self::update_status(
+                    &pool,
+                    media_id,
+                    StreamStatusEnum::Failed,
+                    None,
+                    None,
+                    Some(&format!("{:?}", e)),
+                )
+                .await
+                {
+                    eprintln!("Failed to save HLS stream status: {}", e);
+                }
+            }
+        }));
+
+        Ok(())
+    }
+
+    async fn generate_stream(pool: &PgPool, media_id: i32, filepath: &str) -> Result<(), AppError> {
+        Self::update_status(
+            pool,
+            media_id,
+            StreamStatusEnum::Processing,
+            None,
+            None,
+            None,
+        )
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let metadata = MediaMetadataService::get_metadata_for_media(pool, media_id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let directory = FileService::derived_path(filepath, HLS_DIR, "")
+            .ok_or_else(|| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let source = filepath.to_string();
+        let output = directory.clone();
+
+        let variants = tokio::task::spawn_blocking(move || {
+            Self::transcode(&source, &output, &metadata).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .map_err(AppError::InternalServerError)?;
+
+        Self::update_status(
+            pool,
+            media_id,
+            StreamStatusEnum::Ready,
+            Some(&directory),
+            Some(variants),
+            None,
+        )
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))
+    }
+
+    async fn update_status(
+        pool: &PgPool,
+        media_id: i32,
+        status: StreamStatusEnum,
+        directory: Option<&str>,
+        variants: Option<serde_json::Value>,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+                update media_streams set status = $1, directory = coalesce($2, directory), variants = coalesce($3, variants),
+                error = $4, updated_at = $5, updated_by = $6
+                where deleted_at is null and media_id = $7
+            "#,
+            status as i32,
+            directory,
+            variants,
+            error,
+            now,
+            actor_id,
+            media_id,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Encodes one H.264/AAC rendition per ladder rung the source can fill, then writes
+    /// the master playlist. Everything goes into a scratch directory that replaces the
+    /// old stream only once complete, so players never see half a ladder.
+    pub fn transcode(
+        filepath: &str,
+        directory: &str,
+        metadata: &MediaMetadataModel,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let scratch = format!("{}.partial", directory);
+
+        if Path::new(&scratch).exists() {
+            fs::remove_dir_all(&scratch)?;
+        }
+
+        let (source_width, source_height) = match (
+            metadata.display_width.or(metadata.width),
+            metadata.display_height.or(metadata.height),
+        ) {
+            (Some(w), Some(h)) if w > 0 && h > 0 => (w as u32, h as u32),
+            _ => (1280, 720),
+        };
+
+        let short_side = source_width.min(source_height);
+        let has_audio = metadata.audio_codec.is_some();
+
+        let mut rungs: Vec<(&str, u32, u32)> = HLS_LADDER
+            .iter()
+            .filter(|(_, size, _)| *size <= short_side)
+            .copied()
+            .collect();
+
+        // Sources below the smallest rung get it at their own size.
+        if rungs.is_empty() {
+            let (name, _, bitrate) = HLS_LADDER[HLS_LADDER.len() - 1];
+            rungs.push((name, short_side - short_side % 2, bitrate));
+        }
+
+        let mut master = String::from("#EXTM3U\n#EXT-X-VERSION:3\n");
+        let mut variants = Vec::new();
+
+        for (name, size, bitrate) in rungs {
+            let variant_dir = format!("{}/{}", scratch, name);
+            fs::create_dir_all(&variant_dir)?;
+
+            Self::encode_variant(filepath, &variant_dir, size, bitrate, has_audio)?;
+
+            let (width, height) = Self::scaled_size(source_width, source_height, size);
+            let bandwidth = (bitrate * 107 / 100 + has_audio as u32 * HLS_AUDIO_BITRATE) * 1000;
+
+            master.push_str(&format!(
+                "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={}x{}\n{}/{}\n",
+                bandwidth, width, height, name, HLS_VARIANT_PLAYLIST
+            ));
+
+            variants.push(json!({
+                "name": name,
+                "width": width,
+                "height": height,
+                "bandwidth": bandwidth,
+            }));
+        }
+
+        fs::write(format!("{}/{}", scratch, HLS_MASTER_PLAYLIST), master)?;
+
+        if Path::new(directory).exists() {
+            fs::remove_dir_all(directory)?;
+        }
+
+        fs::rename(&scratch, directory)?;
+
+        Ok(serde_json::Value::Array(variants))
+    }
+
+    fn encode_variant(
+        filepath: &str,
+        variant_dir: &str,
+        size: u32,
+        bitrate: u32,
+        has_audio: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let scale = format!(
+            "scale=w='if(gte(iw,ih),-2,{0})':h='if(gte(iw,ih),{0},-2)'",
+            size
+        );
+        let bitrate_arg = format!("{}k", bitrate);
+        let maxrate = format!("{}k", bitrate * 107 / 100);
+        let bufsize = format!("{}k", bitrate * 3 / 2);
+        let audio_bitrate = format!("{}k", HLS_AUDIO_BITRATE);
+        // Keyframes on segment boundaries keep the renditions switchable mid-stream.
+        let keyframes = format!("expr:gte(t,n_forced*{})", HLS_SEGMENT_SECONDS);
+        let hls_time = HLS_SEGMENT_SECONDS.to_string();
+        let segments = format!("{}/segment_%04d.ts", variant_dir);
+        let playlist = format!("{}/{}", variant_dir, HLS_VARIANT_PLAYLIST);
+
+        let mut args = vec![
+            "-y",
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-i",
+            filepath,
+            "-map",
+            "0:v:0",
+            "-vf",
+            &scale,
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-profile:v",
+            "high",
+            "-pix_fmt",
+            "yuv420p",
+            "-b:v",
+            &bitrate_arg,
+            "-maxrate",
+            &maxrate,
+            "-bufsize",
+            &bufsize,
+            "-force_key_frames",
+            &keyframes,
+            "-sc_threshold",
+            "0",
+        ];
+
+        match has_audio {
+            true => args.extend([
+                "-map",
+                "0:a:0",
+                "-c:a",
+                "aac",
+                "-b:a",
+                &audio_bitrate,
+                "-ac",
+                "2",
+            ]),
+            false => args.push("-an"),
+        }
+
+        args.extend([
+            "-f",
+            "hls",
+            "-hls_time",
+            &hls_time,
+            "-hls_playlist_type",
+            "vod",
+            "-hls_segment_filename",
+            &segments,
+            &playlist,
+        ]);
+
+        let status = Command::new("ffmpeg")
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            return Err("Failed to transcode HLS rendition".into());
+        }
+
+        Ok(())
+    }
+
+    /// Mirrors ffmpeg's `-2`: the long side scaled in proportion, rounded to even.
+    fn scaled_size(width: u32, height: u32, short_side: u32) -> (u32, u32) {
+        let scale = |long: u32, short: u32| {
+            let scaled = (long as f64 * short_side as f64 / short as f64).round() as u32;
+            scaled + scaled % 2
+        };
+
+        match width >= height {
+            true => (scale(width, height), short_side),
+            false => (short_side, scale(height, width)),
+        }
+    }
+
+    /// Path of a playlist or segment within a ready stream, or None for anything that
+    /// is not one of its variants' files.
+    pub fn variant_file(stream: &MediaStreamModel, variant: &str, file: &str) -> Option<String> {
+        let directory = stream.directory.as_deref()?;
+
+        let known = stream
+            .variants
+            .as_ref()?
+            .as_array()?
+            .iter()
+            .any(|v| v["name"].as_str() == Some(variant));
+
+        let segment = file
+            .strip_prefix("segment_")
+            .and_then(|f| f.strip_suffix(".ts"))
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+
+        match known && (segment || file == HLS_VARIANT_PLAYLIST) {
+            true => Some(format!("{}/{}/{}", directory, variant, file)),
+            false => None,
+        }
+    }
+
+    pub fn content_type(file: &str) -> &'static str {
+        match file.ends_with(".m3u8") {
+            true => "application/vnd.apple.mpegurl",
+            false => "video/mp2t",
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use image::{
+    codecs::{avif::AvifEncoder, jpeg::JpegEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, File, FileTimes},
+    io::BufWriter,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Semaphore;
+
+use crate::config::get_image_cache_max_bytes;
+use crate::errors::app_error::AppError;
+use crate::media::{
+    dtos::ImageQueryDto,
+    enums::{image_fit_enum::ImageFitEnum, image_format_enum::ImageFormatEnum},
+    services::PhotoService,
+};
+
+const IMAGE_CACHE_DIR: &str = "./cache/images";
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+const DEFAULT_IMAGE_QUALITY: u8 = 80;
+/// rav1e speed from 1 (slowest) to 10; requests wait on this, so favour speed.
+const AVIF_SPEED: u8 = 8;
+
+/// Decoding a full-size original takes a core and hundreds of megabytes, so cache
+/// misses are rendered a few at a time.
+pub static RENDER_SLOTS: Semaphore = Semaphore::const_new(2);
+
+pub struct ImageService {}
+
+impl ImageService {
+    pub fn validate(query: &ImageQueryDto) -> Result<(), AppError> {
+        for dimension in [query.w, query.h].into_iter().flatten() {
+            if !(1..=MAX_IMAGE_DIMENSION).contains(&dimension) {
+                return Err(AppError::BadRequest(format!(
+                    "Width and height must be between 1 and {}",
+                    MAX_IMAGE_DIMENSION
+                )));
+            }
+        }
+
+        if query.quality.is_some_and(|q| !(1..=100).contains(&q)) {
+            return Err(AppError::BadRequest(
+                "Quality must be between 1 and 100".into(),
+            ));
+        }
+
+        // The WebP encoder is lossless only, so there is no quality to trade.
+        if query.quality.is_some() && query.format.unwrap_or_default() == ImageFormatEnum::Webp {
+            return Err(AppError::BadRequest(
+                "Quality applies to JPEG and AVIF only; WebP is always lossless".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The cached rendering of `filepath` for `query`, if there is one. Entries are keyed
+    /// by the original's content hash, so identical files share them and a replaced
+    /// original never serves a stale image.
+    pub fn find_cached(
+        filepath: &str,
+        hash: Option<&str>,
+        query: &ImageQueryDto,
+    ) -> Option<String> {
+        let cache_path = Self::cache_path(filepath, hash, query);
+        let file = File::options().write(true).open(&cache_path).ok()?;
+
+        // Recency lives in the access time; the modification time feeds the ETag.
+        file.set_times(FileTimes::new().set_accessed(SystemTime::now()))
+            .ok()?;
+
+        Some(cache_path)
+    }
+
+    /// Returns the cached rendering of `filepath` for `query`, rendering it on a miss.
+    /// Callers hold a `RENDER_SLOTS` permit.
+    pub fn get_or_render(
+        filepath: &str,
+        hash: Option<&str>,
+        query: &ImageQueryDto,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(cache_path) = Self::find_cached(filepath, hash, query) {
+            return Ok(cache_path);
+        }
+
+        let cache_path = Self::cache_path(filepath, hash, query);
+
+        fs::create_dir_all(IMAGE_CACHE_DIR)?;
+
+        let img = Self::resize(PhotoService::open_oriented(filepath)?, query);
+
+        // Unique per render, so concurrent misses for one key never share a file.
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let partial_path = format!("{}.{}.partial", cache_path, nonce);
+        Self::encode(&img, &partial_path, query)?;
+        fs::rename(&partial_path, &cache_path)?;
+
+        if let Err(e) = Self::evict(get_image_cache_max_bytes()) {
+            eprintln!("Failed to evict image cache: {:?}", e);
+        }
+
+        Ok(cache_path)
+    }
+
+    fn cache_path(filepath: &str, hash: Option<&str>, query: &ImageQueryDto) -> String {
+        format!(
+            "{}/{}.{}",
+            IMAGE_CACHE_DIR,
+            Self::cache_key(hash.unwrap_or(filepath), query),
+            query.format.unwrap_or_default().extension()
+        )
+    }
+
+    fn resize(img: DynamicImage, query: &ImageQueryDto) -> DynamicImage {
+        let (width, height) = (img.width(), img.height());
+
+        // A single given side scales the other in proportion.
+        let (target_width, target_height) = match (query.w, query.h) {
+            (None, None) => return img,
+            (Some(w), Some(h)) => (w, h),
+            (Some(w), None) => (w, Self::proportional(height, w, width)),
+            (None, Some(h)) => (Self::proportional(width, h, height), h),
+        };
+
+        // Cover and Fill keep the requested shape but never grow past the original.
+        let scale = f64::min(
+            1.0,
+            f64::min(
+                width as f64 / target_width as f64,
+                height as f64 / target_height as f64,
+            ),
+        );
+        let (bounded_width, bounded_height) = (
+            ((target_width as f64 * scale).round() as u32).max(1),
+            ((target_height as f64 * scale).round() as u32).max(1),
+        );
+
+        match query.fit.unwrap_or_default() {
+            ImageFitEnum::Contain if target_width >= width && target_height >= height => img,
+            ImageFitEnum::Contain => img.resize(target_width, target_height, FilterType::Lanczos3),
+            ImageFitEnum::Cover => {
+                img.resize_to_fill(bounded_width, bounded_height, FilterType::Lanczos3)
+            }
+            ImageFitEnum::Fill => {
+                img.resize_exact(bounded_width, bounded_height, FilterType::Lanczos3)
+            }
+        }
+    }
+
+    fn proportional(side: u32, target: u32, other: u32) -> u32 {
+        ((side as u64 * target as u64) / other.max(1) as u64).max(1) as u32
+    }
+
+    fn encode(
+        img: &DynamicImage,
+        path: &str,
+        query: &ImageQueryDto,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let writer = BufWriter::new(File::create(path)?);
+        let quality = query.quality.unwrap_or(DEFAULT_IMAGE_QUALITY);
+
+        match query.format.unwrap_or_default() {
+            ImageFormatEnum::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(writer, quality))?,
+            ImageFormatEnum::Webp => DynamicImage::ImageRgba8(img.to_rgba8())
+                .write_with_encoder(WebPEncoder::new_lossless(writer))?,
+            ImageFormatEnum::Avif => DynamicImage::ImageRgba8(img.to_rgba8()).write_with_encoder(
+                AvifEncoder::new_with_speed_quality(writer, AVIF_SPEED, quality),
+            )?,
+        }
+
+        Ok(())
+    }
+
+    fn cache_key(source: &str, query: &ImageQueryDto) -> String {
+        let params = format!(
+            "{}|{:?}|{:?}|{:?}|{:?}|{}",
+            source,
+            query.w,
+            query.h,
+            query.fit.unwrap_or_default(),
+            query.format.unwrap_or_default(),
+            query.quality.unwrap_or(DEFAULT_IMAGE_QUALITY)
+        );
+
+        format!("{:x}", Sha256::digest(params.as_bytes()))
+    }
+
+    /// Deletes the least recently used entries until the cache fits `max_bytes`.
+    pub fn evict(max_bytes: u64) -> Result<(), std::io::Error> {
+        let mut entries: Vec<(SystemTime, u64, std::path::PathBuf)> =
+            fs::read_dir(IMAGE_CACHE_DIR)?
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let metadata = entry.metadata().ok()?;
+                    let accessed = metadata.accessed().unwrap_or(UNIX_EPOCH);
+
+                    metadata
+                        .is_file()
+                        .then(|| (accessed, metadata.len(), entry.path()))
+                })
+                .collect();
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+
+        if total <= max_bytes {
+            return Ok(());
+        }
+
+        entries.sort_by_key(|(accessed, _, _)| *accessed);
+
+        for (_, size, path) in entries {
+            if total <= max_bytes {
+                break;
+            }
+
+            if path.extension().is_some_and(|e| e == "partial") {
+                continue;
+            }
+
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::auth::services::AuthService;
+use crate::media::{
+    dtos::MediaMetadataUpdatePayloadDto,
+    models::{MediaMetadataAuditModel, MediaMetadataOverrideModel},
+};
+
+pub struct MediaMetadataOverrideService {}
+
+impl MediaMetadataOverrideService {
+    pub async fn get_overrides_for_media(
+        pool: &PgPool,
+        media_id: i32,
+    ) -> Result<Option<MediaMetadataOverrideModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaMetadataOverrideModel,
+            r#"select * from media_metadata_overrides where deleted_at is null and media_id = $1"#,
+            media_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn update_overrides(
+        pool: &PgPool,
+        media_id: i32,
+        payload: MediaMetadataUpdatePayloadDto,
+    ) -> Result<MediaMetadataOverrideModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let mut tx = pool.begin().await?;
+
+        let current = sqlx::query_as!(
+            MediaMetadataOverrideModel,
+            r#"select * from media_metadata_overrides where deleted_at is null and media_id = $1 for update"#,
+            media_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or_default();
+
+        let mut next = current.clone();
+        let mut changes = Vec::new();
+
+        Self::apply(&mut changes, "title", &mut next.title, payload.title);
+        Self::apply(
+            &mut changes,
+            "description",
+            &mut next.description,
+            payload.description,
+        );
+        Self::apply(
+            &mut changes,
+            "latitude",
+            &mut next.latitude,
+            payload.latitude,
+        );
+        Self::apply(
+            &mut changes,
+            "longitude",
+            &mut next.longitude,
+            payload.longitude,
+        );
+        Self::apply(
+            &mut changes,
+            "location_name",
+            &mut next.location_name,
+            payload.location_name,
+        );
+
+        if let Some(taken_at) = payload.taken_at {
+            let old_value = Self::format_taken_at(&current);
+
+            next.taken_at = taken_at.map(|t| t.naive_local());
+            next.taken_at_offset = taken_at.map(|t| t.offset().to_string());
+
+            let new_value = Self::format_taken_at(&next);
+
+            if old_value != new_value {
+                changes.push(("taken_at", json!(old_value), json!(new_value)));
+            }
+        }
+
+        let overrides = sqlx::query_as!(
+            MediaMetadataOverrideModel,
+            r#"
+                insert into media_metadata_overrides
+                (media_id, title, description, taken_at, taken_at_offset, latitude, longitude, location_name, created_at, updated_at, created_by, updated_by)
+                values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, $10, $10)
+                on conflict (media_id) do update set
+                title = excluded.title,
+                description = excluded.description,
+                taken_at = excluded.taken_at,
+                taken_at_offset = excluded.taken_at_offset,
+                latitude = excluded.latitude,
+                longitude = excluded.longitude,
+                location_name = excluded.location_name,
+                deleted_at = null,
+                updated_at = excluded.updated_at,
+                updated_by = excluded.updated_by
+                returning *
+            "#,
+            media_id,
+            next.title,
+            next.description,
+            next.taken_at,
+            next.taken_at_offset,
+            next.latitude,
+            next.longitude,
+            next.location_name,
+            now,
+            actor_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (field, old_value, new_value) in changes {
+            sqlx::query!(
+                r#"insert into media_metadata_audit (media_id, field, old_value, new_value, created_at, created_by) values ($1, $2, $3, $4, $5, $6)"#,
+                media_id,
+                field,
+                old_value,
+                new_value,
+                now,
+                actor_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(overrides)
+    }
+
+    pub async fn get_audit_for_media(
+        pool: &PgPool,
+        media_id: i32,
+    ) -> Result<Vec<MediaMetadataAuditModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaMetadataAuditModel,
+            r#"select * from media_metadata_audit where media_id = $1 order by id desc"#,
+            media_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    fn apply<T: Serialize + PartialEq>(
+        changes: &mut Vec<(&'static str, serde_json::Value, serde_json::Value)>,
+        field: &'static str,
+        target: &mut Option<T>,
+        value: Option<Option<T>>,
+    ) {
+        if let Some(value) = value {
+            if *target != value {
+                changes.push((field, json!(target), json!(value)));
+                *target = value;
+            }
+        }
+    }
+
+    fn format_taken_at(overrides: &MediaMetadataOverrideModel) -> Option<String> {
+        overrides.taken_at.map(|taken_at| {
+            format!(
+                "{}{}",
+                taken_at.format("%Y-%m-%dT%H:%M:%S"),
+                overrides.taken_at_offset.as_deref().unwrap_or_default()
+            )
+        })
+    }
+}
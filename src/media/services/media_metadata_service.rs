@@ -2,7 +2,15 @@ use chrono::Utc;
 use sqlx::PgPool;
 
 use crate::auth::services::AuthService;
-use crate::media::models::{MediaMetadataModel, MediaModel};
+use crate::errors::app_error::AppError;
+use crate::media::{
+    enums::media_type_enum::MediaTypeEnum,
+    models::{MediaMetadataModel, MediaModel},
+    services::{
+        AudioService, DocumentService, FileService, MotionPhotoService, PhotoService, RawService,
+        VideoService, XmpService,
+    },
+};
 
 pub struct MediaMetadataService {}
 
@@ -19,8 +27,11 @@ impl MediaMetadataService {
             MediaMetadataModel,
             r#"
                 insert into media_metadata
-                (media_id, original_filename, mime_type, size, width, height, hash, camera_make, camera_model, focal_length, aperture, taken_at, duration, frame_rate, video_codec, audio_codec, video_bitrate, audio_bitrate, sample_rate, created_at, updated_at, created_by, updated_by)
-                values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $20, $21, $21)
+                (media_id, original_filename, mime_type, size, width, height, hash, camera_make, camera_model, focal_length, aperture, taken_at, duration, frame_rate, video_codec, audio_codec, video_bitrate, audio_bitrate, sample_rate, perceptual_hash, lens_model, iso, exposure_time, content_identifier, motion_video_length, page_count,
+                orientation, taken_at_offset, flash, white_balance, software, exif,
+                title, description, keywords, rating, regions, display_width, display_height, blurhash, created_at, updated_at, created_by, updated_by)
+                values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32,
+                $33, $34, $35, $36, $37, $38, $39, $40, $41, $41, $42, $42)
                 returning *
             "#,
             media.id,
@@ -42,6 +53,27 @@ impl MediaMetadataService {
             metadata.video_bitrate,
             metadata.audio_bitrate,
             metadata.sample_rate,
+            metadata.perceptual_hash,
+            metadata.lens_model,
+            metadata.iso,
+            metadata.exposure_time,
+            metadata.content_identifier,
+            metadata.motion_video_length,
+            metadata.page_count,
+            metadata.orientation,
+            metadata.taken_at_offset,
+            metadata.flash,
+            metadata.white_balance,
+            metadata.software,
+            metadata.exif,
+            metadata.title,
+            metadata.description,
+            metadata.keywords.as_deref(),
+            metadata.rating,
+            metadata.regions,
+            metadata.display_width,
+            metadata.display_height,
+            metadata.blurhash,
             now,
             actor_id,
         )
@@ -65,4 +97,167 @@ impl MediaMetadataService {
 
         Ok(metadata)
     }
+
+    pub async fn update_metadata(
+        pool: &PgPool,
+        media: &MediaModel,
+        metadata: &MediaMetadataModel,
+    ) -> Result<MediaMetadataModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let row = sqlx::query_as!(
+            MediaMetadataModel,
+            r#"
+                update media_metadata set
+                original_filename = coalesce(original_filename, $2), mime_type = $3, size = $4, width = $5, height = $6, hash = $7,
+                camera_make = $8, camera_model = $9, focal_length = $10, aperture = $11, taken_at = $12, duration = $13, frame_rate = $14,
+                video_codec = $15, audio_codec = $16, video_bitrate = $17, audio_bitrate = $18, sample_rate = $19,
+                perceptual_hash = $20, lens_model = $21, iso = $22, exposure_time = $23,
+                content_identifier = $24, motion_video_length = $25, page_count = $26,
+                orientation = $27, taken_at_offset = $28, flash = $29, white_balance = $30, software = $31, exif = $32,
+                title = $33, description = $34, keywords = $35, rating = $36, regions = $37,
+                display_width = $38, display_height = $39, blurhash = $40, updated_at = $41, updated_by = $42
+                where deleted_at is null and media_id = $1
+                returning *
+            "#,
+            media.id,
+            metadata.original_filename,
+            metadata.mime_type,
+            metadata.size,
+            metadata.width,
+            metadata.height,
+            metadata.hash,
+            metadata.camera_make,
+            metadata.camera_model,
+            metadata.focal_length,
+            metadata.aperture,
+            metadata.taken_at,
+            metadata.duration,
+            metadata.frame_rate,
+            metadata.video_codec,
+            metadata.audio_codec,
+            metadata.video_bitrate,
+            metadata.audio_bitrate,
+            metadata.sample_rate,
+            metadata.perceptual_hash,
+            metadata.lens_model,
+            metadata.iso,
+            metadata.exposure_time,
+            metadata.content_identifier,
+            metadata.motion_video_length,
+            metadata.page_count,
+            metadata.orientation,
+            metadata.taken_at_offset,
+            metadata.flash,
+            metadata.white_balance,
+            metadata.software,
+            metadata.exif,
+            metadata.title,
+            metadata.description,
+            metadata.keywords.as_deref(),
+            metadata.rating,
+            metadata.regions,
+            metadata.display_width,
+            metadata.display_height,
+            metadata.blurhash,
+            now,
+            actor_id,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(row),
+            None => Self::create_metadata(pool, media, metadata).await,
+        }
+    }
+
+    /// Decoding, hashing and the external tool calls all block, so extraction runs on
+    /// the blocking pool rather than a runtime worker.
+    pub async fn extract_metadata(
+        filepath: &str,
+        original_filename: &str,
+    ) -> Result<MediaMetadataModel, AppError> {
+        let filepath = filepath.to_string();
+        let original_filename = original_filename.to_string();
+
+        tokio::task::spawn_blocking(move || Self::extract(&filepath, &original_filename))
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?
+    }
+
+    fn extract(filepath: &str, original_filename: &str) -> Result<MediaMetadataModel, AppError> {
+        let mime_type = Self::detect_mime_type(filepath, original_filename)?;
+
+        let size = std::fs::metadata(filepath)
+            .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?
+            .len() as i64;
+
+        let mut metadata = MediaMetadataModel {
+            mime_type: Some(mime_type.to_string()),
+            size: Some(size),
+            original_filename: Some(original_filename.to_string()),
+            ..Default::default()
+        };
+
+        match MediaTypeEnum::from_mime(&mime_type) {
+            MediaTypeEnum::Photo => {
+                PhotoService::extract_photo_metadata(filepath, &mut metadata);
+                (metadata.perceptual_hash, metadata.blurhash) =
+                    PhotoService::generate_hashes(filepath);
+                metadata.motion_video_length = MotionPhotoService::embedded_video_length(filepath);
+            }
+            MediaTypeEnum::Video => {
+                VideoService::extract_video_metadata(filepath, &mut metadata);
+                (metadata.perceptual_hash, metadata.blurhash) =
+                    VideoService::generate_hashes(filepath);
+            }
+            MediaTypeEnum::Audio => {
+                AudioService::extract_audio_metadata(filepath, &mut metadata);
+            }
+            MediaTypeEnum::Document => {
+                DocumentService::extract_document_metadata(filepath, &mime_type, &mut metadata);
+            }
+            MediaTypeEnum::Unknown => {}
+        }
+
+        XmpService::extract_xmp_metadata(filepath, &mime_type, &mut metadata);
+
+        // Orientations 5-8 turn the image by 90 degrees, so it displays with the sides swapped.
+        // Videos already know their display size from the stream's rotation.
+        if metadata.display_width.is_none() {
+            let rotated = metadata.orientation.is_some_and(|o| (5..=8).contains(&o));
+
+            (metadata.display_width, metadata.display_height) = match rotated {
+                true => (metadata.height, metadata.width),
+                false => (metadata.width, metadata.height),
+            };
+        }
+
+        if let Ok(hash) = FileService::generate_file_hash(filepath) {
+            metadata.hash = Some(hash);
+        }
+
+        Ok(metadata)
+    }
+
+    /// Sniffs the content type, letting a camera RAW extension win over the TIFF or
+    /// ISO-BMFF container `infer` sees underneath.
+    pub fn detect_mime_type(filepath: &str, original_filename: &str) -> Result<String, AppError> {
+        let sniffed = infer::get_from_path(filepath)
+            .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?
+            .map(|t| t.mime_type().to_string());
+
+        let raw_mime_type = RawService::raw_mime_type(original_filename).filter(|_| {
+            sniffed.as_deref().is_none_or(|mime| {
+                mime == "image/tiff" || RawService::is_raw(mime) || !mime.starts_with("image/")
+            })
+        });
+
+        Ok(raw_mime_type
+            .map(|mime| mime.to_string())
+            .or(sniffed)
+            .unwrap_or_else(|| "application/octet-stream".to_string()))
+    }
 }
@@ -2,7 +2,10 @@ use chrono::Utc;
 
 use crate::auth::services::AuthService;
 use crate::media::{
-    dtos::{MediaListPayloadDto, MediaListResponseDto, MediaListRow, PaginationMetadataDto},
+    dtos::{
+        MediaArchiveEntryDto, MediaArchivePayloadDto, MediaBulkUpdatePayloadDto,
+        MediaListPayloadDto, MediaListResponseDto, MediaListRow, PaginationMetadataDto,
+    },
     models::MediaModel,
 };
 use crate::user::models::UserModel;
@@ -43,21 +46,86 @@ impl MediaService {
         let limit = payload.limit.unwrap_or(20);
         let offset = payload.offset.unwrap_or(0);
         let user_id = AuthService::id();
+        let archived = payload.archived.unwrap_or(false);
+        let expand_stacks = payload.expand_stacks.unwrap_or(false);
 
+        // A collapsed stack shows as its primary, or as its lowest-id member in this view
+        // while the primary is deleted or archived.
         let media = sqlx::query_as!(
             MediaListRow,
-            r#"select a.*, to_jsonb(b) as media_metadata from media a left join media_metadata b on a.id = b.media_id where a.deleted_at is null and a.user_id = $1 order by a.id desc limit $2 offset $3"#,
+            r#"
+                select a.*, b.display_width as width, b.display_height as height, b.blurhash,
+                to_jsonb(b) as media_metadata, to_jsonb(c) as metadata_overrides,
+                (
+                    select jsonb_agg(jsonb_build_object('id', t.id, 'uuid', t.uuid, 'name', t.name) order by lower(t.name))
+                    from media_tags mt join tags t on t.id = mt.tag_id
+                    where mt.media_id = a.id and t.deleted_at is null
+                ) as tags,
+                (select count(*) from media m where m.stack_id = a.stack_id and m.deleted_at is null) as stack_count
+                from media a left join media_metadata b on a.id = b.media_id
+                left join media_metadata_overrides c on a.id = c.media_id and c.deleted_at is null
+                where a.deleted_at is null and a.user_id = $1
+                and ($4::int is null or exists (select 1 from media_tags mt where mt.media_id = a.id and mt.tag_id = $4))
+                and ($5::bool is null or a.favorite = $5)
+                and ($6::smallint is null or a.rating >= $6)
+                and a.archived = $7
+                and ($8 or a.stack_id is null or a.id = (
+                    select m.id from media m join stacks s on s.id = m.stack_id
+                    where m.stack_id = a.stack_id and m.deleted_at is null and m.archived = $7
+                    order by m.id = s.primary_media_id desc, m.id limit 1
+                ))
+                and not exists (select 1 from media p where p.motion_media_id = a.id and p.deleted_at is null)
+                and ($9::text is null or lower(b.lens_model) = lower($9))
+                and ($10::int is null or b.iso >= $10)
+                and ($11::int is null or b.iso <= $11)
+                order by a.id desc limit $2 offset $3
+            "#,
             user_id,
             limit,
-            offset
+            offset,
+            payload.tag_id,
+            payload.favorite,
+            payload.min_rating,
+            archived,
+            expand_stacks,
+            payload.lens_model,
+            payload.min_iso,
+            payload.max_iso,
         )
         .fetch_all(pool)
         .await?;
 
-        let total = sqlx::query_scalar!(r#"select count(*) from media where deleted_at is null"#)
-            .fetch_one(pool)
-            .await?
-            .unwrap_or(0);
+        let total = sqlx::query_scalar!(
+            r#"
+                select count(*) from media a left join media_metadata b on a.id = b.media_id
+                where a.deleted_at is null and a.user_id = $1
+                and ($2::int is null or exists (select 1 from media_tags mt where mt.media_id = a.id and mt.tag_id = $2))
+                and ($3::bool is null or a.favorite = $3)
+                and ($4::smallint is null or a.rating >= $4)
+                and a.archived = $5
+                and ($6 or a.stack_id is null or a.id = (
+                    select m.id from media m join stacks s on s.id = m.stack_id
+                    where m.stack_id = a.stack_id and m.deleted_at is null and m.archived = $5
+                    order by m.id = s.primary_media_id desc, m.id limit 1
+                ))
+                and not exists (select 1 from media p where p.motion_media_id = a.id and p.deleted_at is null)
+                and ($7::text is null or lower(b.lens_model) = lower($7))
+                and ($8::int is null or b.iso >= $8)
+                and ($9::int is null or b.iso <= $9)
+            "#,
+            user_id,
+            payload.tag_id,
+            payload.favorite,
+            payload.min_rating,
+            archived,
+            expand_stacks,
+            payload.lens_model,
+            payload.min_iso,
+            payload.max_iso,
+        )
+        .fetch_one(pool)
+        .await?
+        .unwrap_or(0);
 
         let response = MediaListResponseDto {
             data: media,
@@ -71,6 +139,187 @@ impl MediaService {
         Ok(response)
     }
 
+    pub async fn bulk_update_media(
+        pool: &sqlx::PgPool,
+        user_id: i32,
+        payload: &MediaBulkUpdatePayloadDto,
+    ) -> Result<u64, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+                update media set
+                favorite = coalesce($1, favorite),
+                rating = coalesce($2, rating),
+                archived = coalesce($3, archived),
+                updated_at = $4,
+                updated_by = $5
+                where deleted_at is null and user_id = $6 and id = any($7)
+            "#,
+            payload.favorite,
+            payload.rating,
+            payload.archived,
+            now,
+            actor_id,
+            user_id,
+            &payload.media_ids,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn list_user_media(
+        pool: &sqlx::PgPool,
+        user_id: i32,
+        media_ids: Option<&[i32]>,
+    ) -> Result<Vec<MediaModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaModel,
+            r#"select * from media where deleted_at is null and user_id = $1 and ($2::int[] is null or id = any($2)) order by id"#,
+            user_id,
+            media_ids as Option<&[i32]>,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Media of every user, for admin maintenance across the whole server.
+    pub async fn list_all_media(
+        pool: &sqlx::PgPool,
+        media_ids: Option<&[i32]>,
+    ) -> Result<Vec<MediaModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaModel,
+            r#"select * from media where deleted_at is null and ($1::int[] is null or id = any($1)) order by id"#,
+            media_ids as Option<&[i32]>,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn list_archive_media(
+        pool: &sqlx::PgPool,
+        user_id: i32,
+        payload: &MediaArchivePayloadDto,
+    ) -> Result<Vec<MediaArchiveEntryDto>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaArchiveEntryDto,
+            r#"
+                select a.id, a.filename, a.filepath, b.original_filename, b.taken_at
+                from media a left join media_metadata b on a.id = b.media_id and b.deleted_at is null
+                where a.deleted_at is null and a.user_id = $1
+                and ($2::int[] is null or a.id = any($2))
+                and ($3::int is null or exists (select 1 from media_tags mt where mt.media_id = a.id and mt.tag_id = $3))
+                and ($4::timestamp is null or b.taken_at >= $4)
+                and ($5::timestamp is null or b.taken_at <= $5)
+                order by b.taken_at nulls last, a.id
+            "#,
+            user_id,
+            payload.media_ids.as_deref() as Option<&[i32]>,
+            payload.tag_id,
+            payload.from,
+            payload.to,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// The user's media whose original has this content hash, if any.
+    pub async fn find_media_by_hash(
+        pool: &sqlx::PgPool,
+        user_id: i32,
+        hash: &str,
+    ) -> Result<Option<MediaModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaModel,
+            r#"
+                select a.* from media a join media_metadata b on a.id = b.media_id
+                where a.deleted_at is null and b.deleted_at is null and a.user_id = $1 and b.hash = $2
+                order by a.id limit 1
+            "#,
+            user_id,
+            hash
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The user's latest media whose original is named `name`, with or without its
+    /// extension; a full-name match wins.
+    pub async fn find_latest_media_by_original_name(
+        pool: &sqlx::PgPool,
+        user_id: i32,
+        name: &str,
+    ) -> Result<Option<MediaModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaModel,
+            r#"
+                select a.* from media a join media_metadata b on a.id = b.media_id
+                where a.deleted_at is null and b.deleted_at is null and a.user_id = $1
+                and (lower(b.original_filename) = lower($2) or lower(regexp_replace(b.original_filename, '\.[^.]*$', '')) = lower($2))
+                order by lower(b.original_filename) = lower($2) desc, a.id desc
+                limit 1
+            "#,
+            user_id,
+            name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn list_stack_media(
+        pool: &sqlx::PgPool,
+        stack_id: i32,
+    ) -> Result<Vec<MediaModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaModel,
+            r#"select * from media where deleted_at is null and stack_id = $1 order by id"#,
+            stack_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn update_media_type(
+        pool: &sqlx::PgPool,
+        id: i32,
+        media_type: i32,
+    ) -> Result<MediaModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            MediaModel,
+            r#"update media set media_type = $1, updated_at = $2, updated_by = $3 where id = $4 returning *"#,
+            media_type,
+            now,
+            actor_id,
+            id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Soft-deletes a media, e.g. one whose referenced original is gone.
+    pub async fn delete_media(pool: &sqlx::PgPool, id: i32) -> Result<(), sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"update media set deleted_at = $1, updated_at = $1, updated_by = $2 where deleted_at is null and id = $3"#,
+            now,
+            actor_id,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn media_detail(pool: &sqlx::PgPool, id: i32) -> Result<MediaModel, sqlx::Error> {
         let media = sqlx::query_as!(
             MediaModel,
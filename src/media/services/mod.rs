@@ -1,15 +1,51 @@
+pub mod archive_service;
+pub mod audio_service;
+pub mod document_service;
 pub mod download_service;
+pub mod duplicate_service;
 pub mod file_service;
+pub mod heif_service;
+pub mod hls_service;
+pub mod image_service;
+pub mod media_metadata_override_service;
 pub mod media_metadata_service;
 pub mod media_service;
+pub mod motion_photo_service;
+pub mod perceptual_hash_service;
 pub mod photo_service;
+pub mod placeholder_service;
+pub mod playback_service;
+pub mod range_service;
+pub mod raw_service;
+pub mod reextract_service;
+pub mod thumbnail_service;
 pub mod upload_service;
+pub mod video_preview_service;
 pub mod video_service;
+pub mod xmp_service;
 
+pub use archive_service::*;
+pub use audio_service::*;
+pub use document_service::*;
 pub use download_service::*;
+pub use duplicate_service::*;
 pub use file_service::*;
+pub use heif_service::*;
+pub use hls_service::*;
+pub use image_service::*;
+pub use media_metadata_override_service::*;
 pub use media_metadata_service::*;
 pub use media_service::*;
+pub use motion_photo_service::*;
+pub use perceptual_hash_service::*;
 pub use photo_service::*;
+pub use placeholder_service::*;
+pub use playback_service::*;
+pub use range_service::*;
+pub use raw_service::*;
+pub use reextract_service::*;
+pub use thumbnail_service::*;
 pub use upload_service::*;
+pub use video_preview_service::*;
 pub use video_service::*;
+pub use xmp_service::*;
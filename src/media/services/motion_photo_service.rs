@@ -0,0 +1,166 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::auth::services::AuthService;
+use crate::media::{
+    enums::media_type_enum::MediaTypeEnum,
+    services::{FileService, XmpService, MOTION_DIR},
+};
+
+/// QuickTime metadata key Apple writes into the video half of a Live Photo.
+pub const QUICKTIME_CONTENT_IDENTIFIER: &str = "com.apple.quicktime.content.identifier";
+
+const APPLE_MAKER_NOTE_PREFIX: &[u8] = b"Apple iOS\0";
+const APPLE_CONTENT_IDENTIFIER_TAG: u16 = 0x0011;
+
+pub struct MotionPhotoService {}
+
+impl MotionPhotoService {
+    /// Reads the Live Photo ContentIdentifier from an Apple maker note. The note is a
+    /// big-endian IFD after a 14-byte header, with offsets relative to the note itself.
+    pub fn apple_content_identifier(maker_note: &[u8]) -> Option<String> {
+        if !maker_note.starts_with(APPLE_MAKER_NOTE_PREFIX) {
+            return None;
+        }
+
+        let read_u16 = |at: usize| {
+            Some(u16::from_be_bytes([
+                *maker_note.get(at)?,
+                *maker_note.get(at + 1)?,
+            ]))
+        };
+        let read_u32 = |at: usize| {
+            Some(u32::from_be_bytes(maker_note.get(at..at + 4)?.try_into().ok()?) as usize)
+        };
+
+        let ifd = 14;
+        let count = read_u16(ifd)? as usize;
+
+        for index in 0..count {
+            let entry = ifd + 2 + index * 12;
+
+            if read_u16(entry)? != APPLE_CONTENT_IDENTIFIER_TAG {
+                continue;
+            }
+
+            let length = read_u32(entry + 4)?;
+            let start = match length > 4 {
+                true => read_u32(entry + 8)?,
+                false => entry + 8,
+            };
+
+            let value = maker_note.get(start..start + length)?;
+            let value = String::from_utf8_lossy(value)
+                .trim_end_matches('\0')
+                .trim()
+                .to_string();
+
+            return Some(value).filter(|v| !v.is_empty());
+        }
+
+        None
+    }
+
+    /// Length of the MP4 appended to a Google Motion Photo, taken from the XMP
+    /// `GCamera:MicroVideoOffset` (v1) or the `MotionPhoto` container item (v2) and
+    /// confirmed by finding an `ftyp` box where the trailer should start.
+    pub fn embedded_video_length(filepath: &str) -> Option<i64> {
+        let data = fs::read(filepath).ok()?;
+        let xmp = XmpService::xmp_packet(&data)?;
+
+        let length = Self::xml_attribute(xmp, "GCamera:MicroVideoOffset").or_else(|| {
+            let semantic = xmp.find("Item:Semantic=\"MotionPhoto\"")?;
+            let open = xmp[..semantic].rfind('<')?;
+            let close = semantic + xmp[semantic..].find('>')?;
+
+            Self::xml_attribute(&xmp[open..close], "Item:Length")
+        })?;
+
+        let length: usize = length.parse().ok().filter(|l| *l > 0)?;
+        let start = data.len().checked_sub(length)?;
+
+        match data.get(start + 4..start + 8) {
+            Some(b"ftyp") => Some(length as i64),
+            _ => None,
+        }
+    }
+
+    /// Writes the trailing MP4 of a Motion Photo to its own file on first use.
+    pub fn extract_embedded_video(
+        filepath: &str,
+        length: i64,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let motion_path =
+            FileService::derived_path(filepath, MOTION_DIR, "mp4").ok_or("Invalid filename")?;
+
+        if Path::new(&motion_path).exists() {
+            return Ok(motion_path);
+        }
+
+        if let Some(parent) = Path::new(&motion_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = File::open(filepath)?;
+        file.seek(SeekFrom::End(-length))?;
+
+        let mut video = Vec::with_capacity(length as usize);
+        file.read_to_end(&mut video)?;
+
+        fs::write(&motion_path, video)?;
+
+        Ok(motion_path)
+    }
+
+    /// Points every still sharing `content_identifier` at the newest matching video, so
+    /// the pair links up whichever half is uploaded last.
+    pub async fn link_live_photo(
+        pool: &PgPool,
+        user_id: i32,
+        content_identifier: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+                update media set motion_media_id = v.id, updated_at = $5, updated_by = $6
+                from (
+                    select a.id from media a join media_metadata b on a.id = b.media_id
+                    where a.deleted_at is null and b.deleted_at is null and a.user_id = $1
+                    and b.content_identifier = $2 and a.media_type = $3
+                    order by a.id desc limit 1
+                ) v
+                where media.deleted_at is null and media.user_id = $1
+                and media.motion_media_id is distinct from v.id
+                and media.id in (
+                    select a.id from media a join media_metadata b on a.id = b.media_id
+                    where b.deleted_at is null and b.content_identifier = $2 and a.media_type = $4
+                )
+            "#,
+            user_id,
+            content_identifier,
+            MediaTypeEnum::Video as i32,
+            MediaTypeEnum::Photo as i32,
+            now,
+            actor_id,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn xml_attribute<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+        let key = format!("{}=\"", name);
+        let start = xml.find(&key)? + key.len();
+        let end = start + xml[start..].find('"')?;
+
+        Some(&xml[start..end])
+    }
+}
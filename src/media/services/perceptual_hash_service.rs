@@ -0,0 +1,261 @@
+use image::{imageops::FilterType, DynamicImage};
+use std::collections::HashMap;
+
+pub struct PerceptualHashService {}
+
+impl PerceptualHashService {
+    /// 64-bit difference hash: shrink to 9x8 grayscale and compare horizontally
+    /// adjacent pixels. Survives resizing and re-compression.
+    pub fn dhash(img: &DynamicImage) -> String {
+        let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+        let mut hash = 0u64;
+        for y in 0..8 {
+            for x in 0..8 {
+                let left = small.get_pixel(x, y)[0];
+                let right = small.get_pixel(x + 1, y)[0];
+
+                hash = (hash << 1) | u64::from(left > right);
+            }
+        }
+
+        format!("{:016x}", hash)
+    }
+
+    pub fn parse(hash: &str) -> Option<u64> {
+        u64::from_str_radix(hash, 16).ok()
+    }
+
+    pub fn distance(a: u64, b: u64) -> u32 {
+        (a ^ b).count_ones()
+    }
+
+    /// Groups hashes whose Hamming distance is within `threshold` (transitively) and
+    /// returns the indices of every group with more than one member.
+    pub fn cluster(hashes: &[u64], threshold: u32) -> Vec<Vec<usize>> {
+        let mut tree = BkTree::default();
+        for (index, hash) in hashes.iter().enumerate() {
+            tree.insert(*hash, index);
+        }
+
+        let mut parents: Vec<usize> = (0..hashes.len()).collect();
+
+        for (index, hash) in hashes.iter().enumerate() {
+            for other in tree.find(*hash, threshold) {
+                let a = Self::root(&mut parents, index);
+                let b = Self::root(&mut parents, other);
+
+                if a != b {
+                    parents[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        for index in 0..hashes.len() {
+            let root = Self::root(&mut parents, index);
+            groups.entry(root).or_default().push(index);
+        }
+
+        let mut clusters: Vec<Vec<usize>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        clusters.sort_by_key(|group| group[0]);
+
+        clusters
+    }
+
+    fn root(parents: &mut [usize], mut index: usize) -> usize {
+        while parents[index] != index {
+            parents[index] = parents[parents[index]];
+            index = parents[index];
+        }
+
+        index
+    }
+}
+
+#[derive(Default)]
+struct BkTree {
+    nodes: Vec<BkNode>,
+}
+
+struct BkNode {
+    hash: u64,
+    index: usize,
+    children: HashMap<u32, usize>,
+}
+
+impl BkTree {
+    fn insert(&mut self, hash: u64, index: usize) {
+        let node = BkNode {
+            hash,
+            index,
+            children: HashMap::new(),
+        };
+
+        if self.nodes.is_empty() {
+            self.nodes.push(node);
+            return;
+        }
+
+        let mut current = 0;
+        loop {
+            let distance = PerceptualHashService::distance(self.nodes[current].hash, hash);
+
+            match self.nodes[current].children.get(&distance) {
+                Some(&child) => current = child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(node);
+                    self.nodes[current].children.insert(distance, child);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn find(&self, hash: u64, threshold: u32) -> Vec<usize> {
+        let mut found = Vec::new();
+
+        if self.nodes.is_empty() {
+            return found;
+        }
+
+        let mut stack = vec![0];
+        while let Some(current) = stack.pop() {
+            let node = &self.nodes[current];
+            let distance = PerceptualHashService::distance(node.hash, hash);
+
+            if distance <= threshold {
+                found.push(node.index);
+            }
+
+            let low = distance.saturating_sub(threshold);
+            let high = distance + threshold;
+
+            for (child_distance, child) in &node.children {
+                if (low..=high).contains(child_distance) {
+                    stack.push(*child);
+                }
+            }
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    /// Brightness falling from left to right, so every pixel outshines its right neighbour.
+    fn gradient(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, _| {
+            Luma([255 - (x * 255 / width) as u8])
+        }))
+    }
+
+    /// Deterministic spread of 64-bit values.
+    fn hashes(count: usize) -> Vec<u64> {
+        let mut state = 0x9e3779b97f4a7c15u64;
+
+        (0..count)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dhash_compares_horizontal_neighbours() {
+        let falling = PerceptualHashService::dhash(&gradient(90, 80));
+        let rising = PerceptualHashService::dhash(&gradient(90, 80).fliph());
+        let flat = PerceptualHashService::dhash(&DynamicImage::new_luma8(90, 80));
+
+        assert_eq!(falling, "ffffffffffffffff");
+        assert_eq!(rising, "0000000000000000");
+        assert_eq!(flat, "0000000000000000");
+    }
+
+    #[test]
+    fn dhash_survives_resizing() {
+        let original = PerceptualHashService::dhash(&gradient(900, 800));
+        let resized = PerceptualHashService::dhash(&gradient(900, 800).thumbnail(120, 120));
+
+        let distance = PerceptualHashService::distance(
+            PerceptualHashService::parse(&original).unwrap(),
+            PerceptualHashService::parse(&resized).unwrap(),
+        );
+
+        assert!(distance <= 2, "distance {}", distance);
+    }
+
+    #[test]
+    fn parse_reads_what_dhash_writes() {
+        assert_eq!(PerceptualHashService::parse("00000000000000ff"), Some(0xff));
+        assert_eq!(PerceptualHashService::parse("not a hash"), None);
+        assert_eq!(PerceptualHashService::distance(0b1011, 0b0110), 3);
+    }
+
+    #[test]
+    fn bk_tree_finds_what_a_linear_scan_finds() {
+        let mut values = hashes(200);
+        // Near neighbours of the first value, so small thresholds have hits too.
+        values.extend([values[0] ^ 1, values[0] ^ 0b110, values[0] ^ 0xf0f]);
+
+        let mut tree = BkTree::default();
+        for (index, hash) in values.iter().enumerate() {
+            tree.insert(*hash, index);
+        }
+
+        for threshold in [0, 1, 3, 8, 24, 32] {
+            for query in [values[0], values[17], values[0] ^ 0b11] {
+                let mut found = tree.find(query, threshold);
+                found.sort();
+
+                let expected: Vec<usize> = (0..values.len())
+                    .filter(|&i| PerceptualHashService::distance(values[i], query) <= threshold)
+                    .collect();
+
+                assert_eq!(found, expected, "threshold {}", threshold);
+            }
+        }
+    }
+
+    #[test]
+    fn cluster_joins_chains_and_drops_singletons() {
+        let base = 0x0123_4567_89ab_cdefu64;
+        let hashes = [
+            base,
+            !base,
+            base ^ 0b01,
+            base ^ 0b11,
+            !base ^ (1 << 40),
+            base ^ 0xffff_0000,
+        ];
+
+        // 0-2 and 2-3 are one bit apart, 0-3 two bits: the chain still makes one group.
+        assert_eq!(
+            PerceptualHashService::cluster(&hashes, 1),
+            vec![vec![0, 2, 3], vec![1, 4]]
+        );
+    }
+
+    #[test]
+    fn cluster_threshold_is_inclusive() {
+        let hashes = [0u64, 0b111, 0b1111];
+
+        assert_eq!(
+            PerceptualHashService::cluster(&hashes, 3),
+            vec![vec![0, 1, 2]]
+        );
+        assert_eq!(PerceptualHashService::cluster(&hashes, 2), vec![vec![1, 2]]);
+        assert!(PerceptualHashService::cluster(&hashes, 0).is_empty());
+    }
+}
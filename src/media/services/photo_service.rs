@@ -1,6 +1,6 @@
-use chrono::{DateTime, NaiveDateTime};
-use exif::{Reader, Tag};
-use image::imageops::FilterType;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Timelike};
+use exif::{Field, In, Reader, Tag, Value};
+use image::{imageops::FilterType, metadata::Orientation, DynamicImage};
 use imageinfo::ImageInfo;
 use std::{
     fs::{self, File},
@@ -9,8 +9,12 @@ use std::{
     str::FromStr,
 };
 
-use crate::media::models::MediaMetadataModel;
-use crate::user::models::UserModel;
+use crate::media::{
+    models::MediaMetadataModel,
+    services::{
+        HeifService, MotionPhotoService, PerceptualHashService, PlaceholderService, RawService,
+    },
+};
 
 pub struct PhotoService {}
 
@@ -33,6 +37,44 @@ impl PhotoService {
             }
         }
 
+        // IFD0 of most RAW containers describes a small thumbnail, not the sensor image.
+        let raw_size = RawService::is_raw_file(path)
+            .then(|| RawService::sensor_size(path))
+            .flatten();
+
+        // A size read off the preview only stands in until the EXIF sub-IFD gives the
+        // sensor's.
+        let mut preview_size = false;
+
+        if let Some((width, height)) = raw_size {
+            metadata.width = Some(width);
+            metadata.height = Some(height);
+        } else if (metadata.width.is_none() && HeifService::is_heif_file(path))
+            || RawService::is_raw_file(path)
+        {
+            preview_size = RawService::is_raw_file(path);
+
+            match Self::decodable_path(path)
+                .and_then(|rendition| Ok(image::image_dimensions(rendition)?))
+            {
+                // RAW renditions are saved upright; the stored size is the sensor's.
+                Ok((width, height))
+                    if RawService::is_raw_file(path)
+                        && Self::read_orientation(path).is_some_and(Self::is_quarter_turn) =>
+                {
+                    metadata.width = Some(height as i32);
+                    metadata.height = Some(width as i32);
+                }
+                Ok((width, height)) => {
+                    metadata.width = Some(width as i32);
+                    metadata.height = Some(height as i32);
+                }
+                Err(e) => {
+                    eprintln!("{:?}", e);
+                }
+            }
+        }
+
         match bufreader.rewind() {
             Ok(_) => {}
             Err(e) => {
@@ -42,48 +84,115 @@ impl PhotoService {
 
         match Reader::new().read_from_container(&mut bufreader) {
             Ok(exif) => {
-                for field in exif.fields() {
+                let mut date_time_original = None;
+                let mut date_time = None;
+                let mut sub_sec = None;
+                let mut offset_time = None;
+                let mut aperture_value = None;
+                let mut tags = serde_json::Map::new();
+
+                for field in exif.fields().filter(|f| f.ifd_num == In::PRIMARY) {
+                    if let Some(value) = Self::exif_json(field) {
+                        tags.insert(field.tag.to_string(), value);
+                    }
+
                     match field.tag {
                         Tag::PixelXDimension => {
-                            if let (Ok(width), None) = (
+                            if let (Ok(width), true) = (
                                 i32::from_str(&field.display_value().to_string()),
-                                metadata.width,
+                                metadata.width.is_none() || preview_size,
                             ) {
                                 metadata.width = Some(width);
                             }
                         }
                         Tag::PixelYDimension => {
-                            if let (Ok(height), None) = (
+                            if let (Ok(height), true) = (
                                 i32::from_str(&field.display_value().to_string()),
-                                metadata.height,
+                                metadata.height.is_none() || preview_size,
                             ) {
                                 metadata.height = Some(height);
                             }
                         }
                         Tag::Make => {
-                            metadata.camera_make = Some(field.display_value().to_string());
+                            metadata.camera_make = Self::exif_ascii(&field.value);
                         }
                         Tag::Model => {
-                            metadata.camera_model = Some(field.display_value().to_string());
+                            metadata.camera_model = Self::exif_ascii(&field.value);
                         }
                         Tag::FocalLength => {
                             metadata.focal_length =
                                 Some(field.display_value().with_unit(&exif).to_string());
                         }
-                        Tag::ApertureValue => {
+                        Tag::FNumber => {
                             metadata.aperture =
                                 Some(field.display_value().with_unit(&exif).to_string());
                         }
-                        Tag::DateTimeOriginal | Tag::DateTime => {
-                            let dt_str = field.display_value().to_string();
-
-                            if let Some(datetime) = Self::parse_exif_datetime(&dt_str) {
-                                metadata.taken_at = Some(datetime);
+                        Tag::ApertureValue => {
+                            aperture_value =
+                                Some(field.display_value().with_unit(&exif).to_string());
+                        }
+                        Tag::LensModel => {
+                            metadata.lens_model = Self::exif_ascii(&field.value);
+                        }
+                        Tag::PhotographicSensitivity => {
+                            metadata.iso = field.value.get_uint(0).map(|iso| iso as i32);
+                        }
+                        Tag::ExposureTime => {
+                            metadata.exposure_time =
+                                Some(field.display_value().with_unit(&exif).to_string());
+                        }
+                        Tag::Orientation => {
+                            metadata.orientation = field
+                                .value
+                                .get_uint(0)
+                                .filter(|o| (1..=8).contains(o))
+                                .map(|o| o as i16);
+                        }
+                        Tag::Flash => {
+                            metadata.flash = Some(field.display_value().to_string());
+                        }
+                        Tag::WhiteBalance => {
+                            metadata.white_balance = Some(field.display_value().to_string());
+                        }
+                        Tag::Software => {
+                            metadata.software = Self::exif_ascii(&field.value);
+                        }
+                        Tag::DateTimeOriginal => {
+                            date_time_original =
+                                Self::parse_exif_datetime(&field.display_value().to_string());
+                        }
+                        Tag::DateTime => {
+                            date_time =
+                                Self::parse_exif_datetime(&field.display_value().to_string());
+                        }
+                        Tag::OffsetTimeOriginal => {
+                            offset_time = Self::exif_ascii(&field.value).or(offset_time);
+                        }
+                        Tag::OffsetTime => {
+                            offset_time = offset_time.or(Self::exif_ascii(&field.value));
+                        }
+                        Tag::MakerNote => {
+                            if let Value::Undefined(bytes, _) = &field.value {
+                                metadata.content_identifier =
+                                    MotionPhotoService::apple_content_identifier(bytes);
                             }
                         }
+                        Tag::SubSecTimeOriginal => {
+                            sub_sec = Self::exif_ascii(&field.value);
+                        }
                         _ => {}
                     }
                 }
+
+                metadata.aperture = metadata.aperture.take().or(aperture_value);
+                metadata.taken_at = date_time_original.or(date_time);
+                metadata.taken_at_offset = offset_time.filter(|o| Self::is_utc_offset(o));
+                metadata.exif = Some(serde_json::Value::Object(tags))
+                    .filter(|t| t.as_object().is_some_and(|t| !t.is_empty()));
+
+                if let (Some(taken_at), Some(sub_sec)) = (metadata.taken_at, sub_sec) {
+                    metadata.taken_at = Self::apply_sub_sec(taken_at, &sub_sec);
+                }
             }
             Err(e) => {
                 eprintln!("{:?}", e);
@@ -93,27 +202,163 @@ impl PhotoService {
 
     pub async fn generate_photo_thumbnail(
         filepath: &str,
-        filename: &str,
-        max_width: u32,
-        user: &UserModel,
+        thumbnail_path: &str,
+        max_size: u32,
+        square: bool,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        let output_path = format!("./uploads/{}/thumbnails", user.uuid);
-        fs::create_dir_all(&output_path)?;
+        let source = filepath.to_string();
+        let destination = thumbnail_path.to_string();
+
+        tokio::task::spawn_blocking(move || -> Result<(), String> {
+            let img = Self::open_oriented(&source).map_err(|e| e.to_string())?;
+
+            let thumbnail = Self::fit_thumbnail(img, max_size, square);
+
+            Self::save_thumbnail(&thumbnail, &destination).map_err(|e| e.to_string())
+        })
+        .await??;
+
+        Ok(thumbnail_path.to_string())
+    }
+
+    /// Scales down to fit `max_size`, or fills and centre-crops a square. Never upscales:
+    /// a photo smaller than the preset keeps its size, or its short side as a square.
+    pub fn fit_thumbnail(img: DynamicImage, max_size: u32, square: bool) -> DynamicImage {
+        if square {
+            let side = max_size.min(img.width()).min(img.height());
+
+            return img.resize_to_fill(side, side, FilterType::Lanczos3);
+        }
+
+        if img.width() <= max_size && img.height() <= max_size {
+            return img;
+        }
+
+        img.resize(max_size, max_size, FilterType::Lanczos3)
+    }
+
+    /// Writes WebP or JPEG by extension. JPEG has no alpha channel, so it is dropped first.
+    pub fn save_thumbnail(
+        img: &DynamicImage,
+        thumbnail_path: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = Path::new(thumbnail_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        match thumbnail_path.ends_with(".jpg") {
+            true => img.to_rgb8().save(thumbnail_path)?,
+            false => img.save(thumbnail_path)?,
+        }
 
-        let stem = Path::new(filename)
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .ok_or("Invalid filename")?;
+        Ok(())
+    }
+
+    /// Perceptual hash and BlurHash placeholder, from a single decode of the photo.
+    pub fn generate_hashes(filepath: &str) -> (Option<String>, Option<String>) {
+        match Self::open_oriented(filepath) {
+            Ok(img) => (
+                Some(PerceptualHashService::dhash(&img)),
+                PlaceholderService::blurhash(&img),
+            ),
+            Err(e) => {
+                eprintln!("{:?}", e);
+                (None, None)
+            }
+        }
+    }
+
+    /// Decodes the photo upright. HEIF renditions are rotated by the decoder and RAW
+    /// renditions are saved with the original's orientation applied, so only other
+    /// photos need turning here.
+    pub fn open_oriented(filepath: &str) -> Result<DynamicImage, Box<dyn std::error::Error>> {
+        let mut img = image::open(Self::decodable_path(filepath)?)?;
+
+        if !HeifService::is_heif_file(filepath) && !RawService::is_raw_file(filepath) {
+            if let Some(orientation) = Self::read_orientation(filepath) {
+                img.apply_orientation(orientation);
+            }
+        }
+
+        Ok(img)
+    }
+
+    pub fn read_orientation(filepath: &str) -> Option<Orientation> {
+        let file = File::open(filepath).ok()?;
+        let exif = Reader::new()
+            .read_from_container(&mut std::io::BufReader::new(file))
+            .ok()?;
+
+        exif.get_field(Tag::Orientation, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .and_then(|orientation| Orientation::from_exif(orientation as u8))
+    }
+
+    /// Orientations 5-8, which swap the sides of the image.
+    fn is_quarter_turn(orientation: Orientation) -> bool {
+        matches!(
+            orientation,
+            Orientation::Rotate90
+                | Orientation::Rotate270
+                | Orientation::Rotate90FlipH
+                | Orientation::Rotate270FlipH
+        )
+    }
+
+    /// `image` cannot decode HEIC/HEIF or camera RAW, so those are read through their
+    /// JPEG rendition.
+    pub fn decodable_path(filepath: &str) -> Result<String, Box<dyn std::error::Error>> {
+        if RawService::is_raw_file(filepath) {
+            return RawService::generate_jpeg_rendition(filepath);
+        }
+
+        if HeifService::is_heif_file(filepath) {
+            return HeifService::generate_jpeg_rendition(filepath);
+        }
+
+        Ok(filepath.to_string())
+    }
+
+    fn exif_ascii(value: &Value) -> Option<String> {
+        match value {
+            Value::Ascii(parts) => parts
+                .first()
+                .map(|part| String::from_utf8_lossy(part).trim().to_string())
+                .filter(|part| !part.is_empty()),
+            _ => None,
+        }
+    }
 
-        let thumbnail_path = format!("{}/{}.webp", output_path, stem);
+    /// Raw tag values for the JSONB dump. Binary blobs such as the maker note are left
+    /// out; they are large and meaningless without a vendor-specific parser.
+    fn exif_json(field: &Field) -> Option<serde_json::Value> {
+        match &field.value {
+            Value::Undefined(bytes, _) if bytes.len() > 64 => None,
+            Value::Ascii(_) => Self::exif_ascii(&field.value).map(serde_json::Value::String),
+            _ => Some(serde_json::Value::String(field.display_value().to_string())),
+        }
+    }
 
-        let img = image::open(filepath)?;
+    /// EXIF offsets look like "+02:00"; anything else is a camera writing garbage.
+    fn is_utc_offset(offset: &str) -> bool {
+        offset.parse::<FixedOffset>().is_ok()
+    }
+
+    /// SubSecTime tags hold the fractional digits of the second, e.g. "07" is 70ms.
+    fn apply_sub_sec(taken_at: NaiveDateTime, sub_sec: &str) -> Option<NaiveDateTime> {
+        let digits: String = sub_sec
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .take(9)
+            .collect();
 
-        let thumbnail = img.resize(max_width, max_width, FilterType::Lanczos3);
+        if digits.is_empty() {
+            return Some(taken_at);
+        }
 
-        thumbnail.save(&thumbnail_path)?;
+        let nanos = format!("{:0<9}", digits).parse::<u32>().ok()?;
 
-        Ok(thumbnail_path)
+        taken_at.with_nanosecond(nanos).or(Some(taken_at))
     }
 
     fn parse_exif_datetime(dt_str: &str) -> Option<NaiveDateTime> {
@@ -136,3 +381,68 @@ impl PhotoService {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::jpeg::JpegEncoder, GenericImageView, Rgb, RgbImage};
+
+    /// A 320x160 JPEG tagged with EXIF orientation 6, saved under a RAW extension so it
+    /// takes the RAW path with itself as the embedded preview. Noise keeps it above the
+    /// minimum preview size.
+    fn orientation_6_raw(name: &str) -> (std::path::PathBuf, String) {
+        let dir =
+            std::env::temp_dir().join(format!("photo-service-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut seed = 1u32;
+        let img = RgbImage::from_fn(320, 160, |_, _| {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let [r, g, b, _] = seed.to_le_bytes();
+            Rgb([r, g, b])
+        });
+
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, 95)
+            .encode_image(&img)
+            .unwrap();
+
+        // Little-endian TIFF header and an IFD0 holding only Orientation (0x0112) = 6.
+        let mut exif = b"Exif\0\0II*\0\x08\0\0\0\x01\0".to_vec();
+        exif.extend([0x12, 0x01, 3, 0, 1, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut file = jpeg[..2].to_vec();
+        file.extend([0xFF, 0xE1]);
+        file.extend((exif.len() as u16 + 2).to_be_bytes());
+        file.extend(exif);
+        file.extend(&jpeg[2..]);
+
+        let path = dir.join("IMG_0001.dng");
+        fs::write(&path, file).unwrap();
+
+        let path = path.to_str().unwrap().to_string();
+        (dir, path)
+    }
+
+    #[test]
+    fn raw_photo_is_turned_upright_once() {
+        let (dir, path) = orientation_6_raw("upright");
+
+        let img = PhotoService::open_oriented(&path).unwrap();
+        let _ = fs::remove_dir_all(dir);
+
+        assert_eq!(img.dimensions(), (160, 320));
+    }
+
+    #[test]
+    fn raw_photo_size_is_sensor_oriented() {
+        let (dir, path) = orientation_6_raw("size");
+
+        let mut metadata = MediaMetadataModel::default();
+        PhotoService::extract_photo_metadata(&path, &mut metadata);
+        let _ = fs::remove_dir_all(dir);
+
+        assert_eq!(metadata.orientation, Some(6));
+        assert_eq!((metadata.width, metadata.height), (Some(320), Some(160)));
+    }
+}
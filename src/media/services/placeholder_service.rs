@@ -0,0 +1,39 @@
+use image::{imageops::FilterType, DynamicImage};
+
+/// Components along the longer side; the shorter side gets proportionally fewer.
+const BLURHASH_COMPONENTS: u32 = 4;
+const BLURHASH_SAMPLE_SIZE: u32 = 64;
+
+pub struct PlaceholderService {}
+
+impl PlaceholderService {
+    /// BlurHash of the image as displayed. Encoding cost grows with the pixel count, so
+    /// it runs on a small copy; the placeholder is blurred anyway.
+    pub fn blurhash(img: &DynamicImage) -> Option<String> {
+        let small = img
+            .resize(
+                BLURHASH_SAMPLE_SIZE,
+                BLURHASH_SAMPLE_SIZE,
+                FilterType::Triangle,
+            )
+            .to_rgba8();
+
+        let (width, height) = small.dimensions();
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let shorter = |long: u32, short: u32| {
+            (BLURHASH_COMPONENTS * short)
+                .div_ceil(long)
+                .clamp(1, BLURHASH_COMPONENTS)
+        };
+
+        let (components_x, components_y) = match width >= height {
+            true => (BLURHASH_COMPONENTS, shorter(width, height)),
+            false => (shorter(height, width), BLURHASH_COMPONENTS),
+        };
+
+        blurhash::encode(components_x, components_y, width, height, small.as_raw()).ok()
+    }
+}
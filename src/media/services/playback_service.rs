@@ -0,0 +1,186 @@
+use serde_json::json;
+use sqlx::PgPool;
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::errors::app_error::AppError;
+use crate::job::{enums::job_type_enum::JobTypeEnum, models::JobModel, services::JobService};
+use crate::media::{
+    models::{MediaMetadataModel, MediaModel},
+    services::{FileService, MediaMetadataService, PLAYBACK_DIR, TRANSCODE_SLOTS},
+};
+use crate::user::models::UserModel;
+
+/// What every current browser plays natively. Anything else gets a playback rendition.
+const WEB_CONTAINERS: [&str; 3] = ["video/mp4", "video/webm", "video/x-m4v"];
+const WEB_VIDEO_CODECS: [&str; 4] = ["h264", "vp8", "vp9", "av1"];
+const WEB_AUDIO_CODECS: [&str; 4] = ["aac", "mp3", "opus", "vorbis"];
+
+/// Streams that can be copied into the MP4 as they are; the rest is re-encoded.
+const MP4_COPY_VIDEO_CODECS: [&str; 1] = ["h264"];
+const MP4_COPY_AUDIO_CODECS: [&str; 2] = ["aac", "mp3"];
+
+pub struct PlaybackService {}
+
+impl PlaybackService {
+    /// Whether a video needs a playback rendition: HEVC, ProRes, MPEG-2, AC3 audio or
+    /// a container like AVI or QuickTime that browsers will not open.
+    pub fn needs_playback(metadata: &MediaMetadataModel) -> bool {
+        let supported = |codec: &Option<String>, codecs: &[&str]| {
+            codec
+                .as_deref()
+                .is_none_or(|codec| codecs.contains(&codec.to_lowercase().as_str()))
+        };
+
+        let container = metadata
+            .mime_type
+            .as_deref()
+            .is_some_and(|mime| WEB_CONTAINERS.contains(&mime));
+
+        !(container
+            && supported(&metadata.video_codec, &WEB_VIDEO_CODECS)
+            && supported(&metadata.audio_codec, &WEB_AUDIO_CODECS))
+    }
+
+    /// Queues a transcode unless one is already pending or running for this media.
+    pub async fn request_playback(
+        pool: &PgPool,
+        user: &UserModel,
+        media: &MediaModel,
+    ) -> Result<JobModel, AppError> {
+        let payload = json!({ "media_id": media.id });
+
+        if let Some(job) =
+            JobService::find_active_job(pool, user.id, JobTypeEnum::TranscodePlayback, &payload)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        {
+            return Ok(job);
+        }
+
+        let job = JobService::create_job(pool, user, JobTypeEnum::TranscodePlayback, Some(payload))
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let task_pool = pool.clone();
+        let job_id = job.id;
+        let media_id = media.id;
+        let filepath = media.filepath.clone();
+
+        JobService::spawn(pool.clone(), user.clone(), &job, async move {
+            Self::generate_playback(&task_pool, job_id, media_id, &filepath).await
+        });
+
+        Ok(job)
+    }
+
+    async fn generate_playback(
+        pool: &PgPool,
+        job_id: i32,
+        media_id: i32,
+        filepath: &str,
+    ) -> Result<Option<serde_json::Value>, AppError> {
+        let _slot = TRANSCODE_SLOTS.acquire().await;
+
+        JobService::mark_running(pool, job_id, 1)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let metadata = MediaMetadataService::get_metadata_for_media(pool, media_id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let source = filepath.to_string();
+
+        let playback_path = tokio::task::spawn_blocking(move || {
+            Self::transcode(&source, &metadata).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .map_err(AppError::InternalServerError)?;
+
+        let _ = JobService::update_progress(pool, job_id, 1).await;
+
+        Ok(Some(json!({
+            "media_id": media_id,
+            "filepath": playback_path,
+        })))
+    }
+
+    /// Writes an H.264/AAC MP4 next to the original, copying whichever streams are
+    /// already compatible. The moov atom goes first so playback starts before the
+    /// whole file has downloaded.
+    pub fn transcode(
+        filepath: &str,
+        metadata: &MediaMetadataModel,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let playback_path =
+            FileService::derived_path(filepath, PLAYBACK_DIR, "mp4").ok_or("Invalid filename")?;
+        let partial_path = format!("{}.partial.mp4", &playback_path[..playback_path.len() - 4]);
+
+        if let Some(parent) = Path::new(&playback_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let copy = |codec: &Option<String>, codecs: &[&str]| {
+            codec
+                .as_deref()
+                .is_some_and(|codec| codecs.contains(&codec.to_lowercase().as_str()))
+        };
+
+        let mut args = vec![
+            "-y",
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-i",
+            filepath,
+            "-map",
+            "0:v:0",
+            "-map",
+            "0:a:0?",
+        ];
+
+        match copy(&metadata.video_codec, &MP4_COPY_VIDEO_CODECS) {
+            true => args.extend(["-c:v", "copy"]),
+            false => args.extend([
+                "-c:v",
+                "libx264",
+                "-preset",
+                "veryfast",
+                "-crf",
+                "23",
+                "-profile:v",
+                "high",
+                "-pix_fmt",
+                "yuv420p",
+            ]),
+        }
+
+        match copy(&metadata.audio_codec, &MP4_COPY_AUDIO_CODECS) {
+            true => args.extend(["-c:a", "copy"]),
+            false => args.extend(["-c:a", "aac", "-b:a", "160k", "-ac", "2"]),
+        }
+
+        args.extend(["-movflags", "+faststart", &partial_path]);
+
+        let status = Command::new("ffmpeg")
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            let _ = fs::remove_file(&partial_path);
+            return Err("Failed to transcode playback rendition".into());
+        }
+
+        fs::rename(&partial_path, &playback_path)?;
+
+        Ok(playback_path)
+    }
+}
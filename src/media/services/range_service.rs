@@ -0,0 +1,214 @@
+use hyper::HeaderMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, PartialEq)]
+pub enum ByteRange {
+    /// No usable `Range`; the whole file goes out with 200.
+    Full,
+    /// Inclusive first and last byte of a single satisfiable range.
+    Partial(u64, u64),
+    /// The range starts past the end of the file; answered with 416.
+    Unsatisfiable,
+}
+
+pub struct RangeService {}
+
+impl RangeService {
+    /// Parses a `Range` header against the file size. Requests for several ranges are
+    /// answered with the full file, which RFC 9110 allows in place of multipart bodies;
+    /// players only ever ask for one. Malformed headers are ignored the same way.
+    pub fn parse_range(header: &str, file_size: u64) -> ByteRange {
+        let Some(spec) = header.trim().strip_prefix("bytes=") else {
+            return ByteRange::Full;
+        };
+
+        let ranges: Vec<&str> = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .collect();
+
+        let [range] = ranges[..] else {
+            return ByteRange::Full;
+        };
+
+        let Some((first, last)) = range.split_once('-') else {
+            return ByteRange::Full;
+        };
+
+        let (first, last) = (first.trim(), last.trim());
+
+        // `bytes=-500` asks for the final 500 bytes.
+        if first.is_empty() {
+            return match last.parse::<u64>() {
+                Ok(0) => ByteRange::Unsatisfiable,
+                Ok(_) if file_size == 0 => ByteRange::Unsatisfiable,
+                Ok(suffix) => ByteRange::Partial(file_size.saturating_sub(suffix), file_size - 1),
+                Err(_) => ByteRange::Full,
+            };
+        }
+
+        let Ok(start) = first.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+
+        if start >= file_size {
+            return ByteRange::Unsatisfiable;
+        }
+
+        let end = match last.is_empty() {
+            true => file_size - 1,
+            false => match last.parse::<u64>() {
+                Ok(end) if end >= start => end.min(file_size - 1),
+                _ => return ByteRange::Full,
+            },
+        };
+
+        ByteRange::Partial(start, end)
+    }
+
+    /// Strong validator from the file's size and modification time, which change
+    /// whenever a rendition or extracted file is rewritten.
+    pub fn etag(file_size: u64, modified: SystemTime) -> String {
+        let modified = modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
+
+        format!("\"{:x}-{:x}\"", file_size, modified)
+    }
+
+    pub fn last_modified(modified: SystemTime) -> String {
+        httpdate::fmt_http_date(modified)
+    }
+
+    /// `If-None-Match` uses the weak comparison, so a `W/` prefix added by a proxy
+    /// still matches.
+    pub fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+        headers
+            .get_all("if-none-match")
+            .iter()
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    }
+
+    /// Whether a conditional GET can be answered with 304. `If-Modified-Since` only
+    /// counts when the client sent no `If-None-Match`.
+    pub fn not_modified(headers: &HeaderMap, etag: &str, modified: SystemTime) -> bool {
+        if headers.contains_key("if-none-match") {
+            return Self::etag_matches(headers, etag);
+        }
+
+        headers
+            .get("if-modified-since")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| httpdate::parse_http_date(h).ok())
+            .is_some_and(|since| Self::truncate(modified) <= since)
+    }
+
+    /// Whether the `Range` still applies. A client resuming a download sends the
+    /// validator it saw; when the file changed since, it gets the whole file instead of
+    /// a piece of the new one. Only strong ETags and exact dates count.
+    pub fn if_range_matches(headers: &HeaderMap, etag: &str, modified: SystemTime) -> bool {
+        let Some(value) = headers.get("if-range").and_then(|h| h.to_str().ok()) else {
+            return headers.get("if-range").is_none();
+        };
+
+        let value = value.trim();
+
+        match value.starts_with('"') {
+            true => value == etag,
+            false => {
+                httpdate::parse_http_date(value).is_ok_and(|date| date == Self::truncate(modified))
+            }
+        }
+    }
+
+    /// HTTP dates have whole-second precision.
+    fn truncate(time: SystemTime) -> SystemTime {
+        let seconds = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_ranges() {
+        assert_eq!(
+            RangeService::parse_range("bytes=0-99", 1000),
+            ByteRange::Partial(0, 99)
+        );
+        assert_eq!(
+            RangeService::parse_range("bytes=500-", 1000),
+            ByteRange::Partial(500, 999)
+        );
+        assert_eq!(
+            RangeService::parse_range("bytes=900-5000", 1000),
+            ByteRange::Partial(900, 999)
+        );
+    }
+
+    #[test]
+    fn parses_suffix_ranges() {
+        assert_eq!(
+            RangeService::parse_range("bytes=-100", 1000),
+            ByteRange::Partial(900, 999)
+        );
+        assert_eq!(
+            RangeService::parse_range("bytes=-5000", 1000),
+            ByteRange::Partial(0, 999)
+        );
+    }
+
+    #[test]
+    fn unsatisfiable_ranges_get_416() {
+        assert_eq!(
+            RangeService::parse_range("bytes=1000-", 1000),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            RangeService::parse_range("bytes=2000-3000", 1000),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            RangeService::parse_range("bytes=-0", 1000),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            RangeService::parse_range("bytes=-100", 0),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            RangeService::parse_range("bytes=0-", 0),
+            ByteRange::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn multiple_or_malformed_ranges_get_the_whole_file() {
+        for header in [
+            "bytes=0-1,5-6",
+            "bytes=abc",
+            "bytes=5-2",
+            "bytes=-x",
+            "items=0-1",
+            "bytes=",
+        ] {
+            assert_eq!(
+                RangeService::parse_range(header, 1000),
+                ByteRange::Full,
+                "{}",
+                header
+            );
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use image::metadata::Orientation;
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::media::services::{FileService, PhotoService, RENDITION_DIR};
+
+const RAW_MIME_TYPES: [(&str, &str); 11] = [
+    ("dng", "image/x-adobe-dng"),
+    ("cr2", "image/x-canon-cr2"),
+    ("cr3", "image/x-canon-cr3"),
+    ("nef", "image/x-nikon-nef"),
+    ("nrw", "image/x-nikon-nrw"),
+    ("arw", "image/x-sony-arw"),
+    ("raf", "image/x-fuji-raf"),
+    ("orf", "image/x-olympus-orf"),
+    ("rw2", "image/x-panasonic-rw2"),
+    ("pef", "image/x-pentax-pef"),
+    ("srw", "image/x-samsung-srw"),
+];
+
+/// Previews smaller than this are usually the 160x120 EXIF thumbnail.
+const MIN_PREVIEW_BYTES: usize = 32 * 1024;
+
+pub struct RawService {}
+
+impl RawService {
+    /// `infer` reports most RAW containers as TIFF or not at all, so the extension decides.
+    pub fn raw_mime_type(filename: &str) -> Option<&'static str> {
+        let extension = Path::new(filename)
+            .extension()
+            .and_then(|e| e.to_str())?
+            .to_lowercase();
+
+        RAW_MIME_TYPES
+            .iter()
+            .find(|(ext, _)| *ext == extension)
+            .map(|(_, mime)| *mime)
+    }
+
+    pub fn is_raw(mime_type: &str) -> bool {
+        RAW_MIME_TYPES.iter().any(|(_, mime)| *mime == mime_type)
+    }
+
+    pub fn is_raw_file(filepath: &str) -> bool {
+        Self::raw_mime_type(filepath).is_some()
+    }
+
+    /// Writes the largest JPEG preview embedded in the RAW file as its rendition.
+    pub fn generate_jpeg_rendition(filepath: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let rendition_path =
+            FileService::derived_path(filepath, RENDITION_DIR, "jpg").ok_or("Invalid filename")?;
+
+        if Path::new(&rendition_path).exists() {
+            return Ok(rendition_path);
+        }
+
+        if let Some(parent) = Path::new(&rendition_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let preview = match Self::extract_with_exiftool(filepath) {
+            Some(preview) => preview,
+            None => Self::find_embedded_jpeg(&fs::read(filepath)?)
+                .ok_or("No embedded preview found")?
+                .to_vec(),
+        };
+
+        let img = image::load_from_memory_with_format(&preview, image::ImageFormat::Jpeg)?;
+
+        // The preview is stored sensor-side up; bake the RAW's orientation into it.
+        match PhotoService::read_orientation(filepath).filter(|o| *o != Orientation::NoTransforms) {
+            Some(orientation) => {
+                let mut img = img;
+                img.apply_orientation(orientation);
+                img.to_rgb8().save(&rendition_path)?;
+            }
+            None => fs::write(&rendition_path, preview)?,
+        }
+
+        Ok(rendition_path)
+    }
+
+    /// Width and height of the sensor image, as exiftool reports them. Previews are
+    /// often smaller, and IFD0 of most containers describes a thumbnail.
+    pub fn sensor_size(filepath: &str) -> Option<(i32, i32)> {
+        let output = Command::new("exiftool")
+            .args(["-n", "-s3", "-ImageWidth", "-ImageHeight", filepath])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+
+        let output = String::from_utf8(output.stdout).ok()?;
+        let mut values = output.lines().map(|line| line.trim().parse::<i32>().ok());
+
+        match (values.next()??, values.next()??) {
+            (width, height) if width > 0 && height > 0 => Some((width, height)),
+            _ => None,
+        }
+    }
+
+    fn extract_with_exiftool(filepath: &str) -> Option<Vec<u8>> {
+        ["-JpgFromRaw", "-PreviewImage"]
+            .iter()
+            .filter_map(|tag| {
+                Command::new("exiftool")
+                    .args(["-b", tag, filepath])
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::null())
+                    .output()
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .map(|output| output.stdout)
+            })
+            .find(|preview| preview.len() >= MIN_PREVIEW_BYTES)
+    }
+
+    /// Scans for JPEG streams by walking their marker segments and returns the largest.
+    fn find_embedded_jpeg(data: &[u8]) -> Option<&[u8]> {
+        let mut best: Option<&[u8]> = None;
+        let mut start = 0;
+
+        while let Some(offset) = data[start..]
+            .windows(3)
+            .position(|w| w == [0xFF, 0xD8, 0xFF])
+        {
+            let soi = start + offset;
+
+            match Self::jpeg_end(data, soi) {
+                Some(end) => {
+                    let candidate = &data[soi..end];
+
+                    if best.is_none_or(|b| candidate.len() > b.len()) {
+                        best = Some(candidate);
+                    }
+
+                    start = end;
+                }
+                None => start = soi + 2,
+            }
+        }
+
+        best.filter(|b| b.len() >= MIN_PREVIEW_BYTES)
+    }
+
+    fn jpeg_end(data: &[u8], soi: usize) -> Option<usize> {
+        let mut pos = soi + 2;
+
+        loop {
+            if *data.get(pos)? != 0xFF {
+                return None;
+            }
+
+            let marker = *data.get(pos + 1)?;
+
+            match marker {
+                0xD9 => return Some(pos + 2),
+                0xD0..=0xD7 | 0x01 => pos += 2,
+                0xFF => pos += 1,
+                0xDA => {
+                    let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]);
+                    pos += 2 + length as usize;
+
+                    // Entropy-coded data: skip stuffed bytes and restart markers.
+                    loop {
+                        let byte = *data.get(pos)?;
+                        let next = *data.get(pos + 1)?;
+
+                        if byte == 0xFF && next != 0x00 && !(0xD0..=0xD7).contains(&next) {
+                            break;
+                        }
+
+                        pos += 1;
+                    }
+                }
+                _ => {
+                    let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]);
+                    pos += 2 + length as usize;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::errors::app_error::AppError;
+use crate::job::services::JobService;
+use crate::media::{
+    enums::media_type_enum::MediaTypeEnum,
+    models::{MediaMetadataModel, MediaModel},
+    services::{MediaMetadataService, MediaService, MotionPhotoService},
+};
+
+pub struct ReextractService {}
+
+impl ReextractService {
+    pub async fn reextract_metadata(
+        pool: &PgPool,
+        job_id: i32,
+        user_id: i32,
+        media_ids: Option<Vec<i32>>,
+    ) -> Result<Option<serde_json::Value>, AppError> {
+        let media = MediaService::list_user_media(pool, user_id, media_ids.as_deref())
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        Self::reextract_all(pool, job_id, &media).await
+    }
+
+    /// Like `reextract_metadata`, across every user's library.
+    pub async fn reextract_server_metadata(
+        pool: &PgPool,
+        job_id: i32,
+        media_ids: Option<Vec<i32>>,
+    ) -> Result<Option<serde_json::Value>, AppError> {
+        let media = MediaService::list_all_media(pool, media_ids.as_deref())
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        Self::reextract_all(pool, job_id, &media).await
+    }
+
+    async fn reextract_all(
+        pool: &PgPool,
+        job_id: i32,
+        media: &[MediaModel],
+    ) -> Result<Option<serde_json::Value>, AppError> {
+        JobService::mark_running(pool, job_id, media.len() as i32)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let mut failed = Vec::new();
+
+        for (index, item) in media.iter().enumerate() {
+            if let Err(e) = Self::reextract_media_metadata(pool, item).await {
+                warn!(
+                    "Metadata re-extraction failed: media_id={}, reason={:?}",
+                    item.id, e
+                );
+
+                failed.push(item.id);
+            }
+
+            let _ = JobService::update_progress(pool, job_id, index as i32 + 1).await;
+        }
+
+        Ok(Some(json!({
+            "processed": media.len(),
+            "failed": failed,
+        })))
+    }
+
+    pub async fn reextract_media_metadata(
+        pool: &PgPool,
+        media: &MediaModel,
+    ) -> Result<MediaMetadataModel, AppError> {
+        let original_filename = MediaMetadataService::get_metadata_for_media(pool, media.id)
+            .await
+            .ok()
+            .and_then(|m| m.original_filename)
+            .unwrap_or_else(|| media.filename.clone());
+
+        let metadata =
+            MediaMetadataService::extract_metadata(&media.filepath, &original_filename).await?;
+
+        let media_type =
+            MediaTypeEnum::from_mime(metadata.mime_type.as_deref().unwrap_or_default());
+
+        if media_type != media.media_type {
+            MediaService::update_media_type(pool, media.id, media_type as i32)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        let metadata = MediaMetadataService::update_metadata(pool, media, &metadata)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if let Some(content_identifier) = &metadata.content_identifier {
+            MotionPhotoService::link_live_photo(pool, media.user_id, content_identifier)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        Ok(metadata)
+    }
+}
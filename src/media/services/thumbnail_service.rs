@@ -0,0 +1,264 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use std::path::Path;
+
+use crate::auth::services::AuthService;
+use crate::errors::app_error::AppError;
+use crate::media::{
+    enums::media_type_enum::MediaTypeEnum,
+    models::{MediaModel, MediaRenditionModel, RenditionPresetModel},
+    services::{AudioService, DocumentService, PhotoService, VideoService},
+};
+use crate::user::models::UserModel;
+
+pub const DEFAULT_THUMBNAIL_PRESET: &str = "grid";
+
+const AUDIO_ICON: &[u8] = include_bytes!("../../../assets/icons/audio.svg");
+const DOCUMENT_ICON: &[u8] = include_bytes!("../../../assets/icons/document.svg");
+const FILE_ICON: &[u8] = include_bytes!("../../../assets/icons/file.svg");
+
+pub struct ThumbnailService {}
+
+impl ThumbnailService {
+    pub async fn find_preset(
+        pool: &PgPool,
+        name: &str,
+    ) -> Result<Option<RenditionPresetModel>, sqlx::Error> {
+        sqlx::query_as!(
+            RenditionPresetModel,
+            r#"select * from rendition_presets where deleted_at is null and name = $1"#,
+            name
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn list_ingest_presets(
+        pool: &PgPool,
+    ) -> Result<Vec<RenditionPresetModel>, sqlx::Error> {
+        sqlx::query_as!(
+            RenditionPresetModel,
+            r#"select * from rendition_presets where deleted_at is null and generate_on_ingest order by max_size"#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_rendition(
+        pool: &PgPool,
+        media_id: i32,
+        preset_id: i32,
+    ) -> Result<Option<MediaRenditionModel>, sqlx::Error> {
+        sqlx::query_as!(
+            MediaRenditionModel,
+            r#"select * from media_renditions where deleted_at is null and media_id = $1 and preset_id = $2"#,
+            media_id,
+            preset_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Retires every stored rendition of a media, returning them so their files can be
+    /// removed. Ingest presets are rendered again by the caller; the rest on next request.
+    pub async fn delete_renditions(
+        pool: &PgPool,
+        media_id: i32,
+    ) -> Result<Vec<MediaRenditionModel>, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            MediaRenditionModel,
+            r#"update media_renditions set deleted_at = $1, updated_at = $1, updated_by = $2 where deleted_at is null and media_id = $3 returning *"#,
+            now,
+            actor_id,
+            media_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns the stored rendition when its file is still on disk and was rendered after
+    /// the preset's last edit, generating it otherwise.
+    pub async fn get_or_generate_rendition(
+        pool: &PgPool,
+        media: &MediaModel,
+        user: &UserModel,
+        preset: &RenditionPresetModel,
+    ) -> Result<MediaRenditionModel, AppError> {
+        let existing = Self::find_rendition(pool, media.id, preset.id)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        match existing {
+            Some(rendition)
+                if Path::new(&rendition.filepath).exists()
+                    && preset.updated_at <= rendition.updated_at =>
+            {
+                Ok(rendition)
+            }
+            _ => Self::generate_rendition(pool, media, user, preset).await,
+        }
+    }
+
+    pub async fn generate_rendition(
+        pool: &PgPool,
+        media: &MediaModel,
+        user: &UserModel,
+        preset: &RenditionPresetModel,
+    ) -> Result<MediaRenditionModel, AppError> {
+        let thumbnail_path = Self::thumbnail_path(user, &media.filename, preset)
+            .ok_or_else(|| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let thumbnail_path = Self::generate_thumbnail(
+            &media.media_type,
+            &media.filepath,
+            &thumbnail_path,
+            preset.max_size as u32,
+            preset.square,
+        )
+        .await
+        .map_err(|_| AppError::InternalServerError("Failed to generate thumbnail".into()))?;
+
+        let (width, height) = image::image_dimensions(&thumbnail_path)
+            .map(|(w, h)| (Some(w as i32), Some(h as i32)))
+            .unwrap_or((None, None));
+
+        let size = std::fs::metadata(&thumbnail_path)
+            .ok()
+            .map(|m| m.len() as i64);
+
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            MediaRenditionModel,
+            r#"
+                insert into media_renditions
+                (media_id, preset_id, filepath, content_type, width, height, size, created_at, updated_at, created_by, updated_by)
+                values ($1, $2, $3, $4, $5, $6, $7, $8, $8, $9, $9)
+                on conflict (media_id, preset_id) do update set
+                filepath = excluded.filepath, content_type = excluded.content_type, width = excluded.width,
+                height = excluded.height, size = excluded.size, updated_at = excluded.updated_at,
+                updated_by = excluded.updated_by, deleted_at = null
+                returning *
+            "#,
+            media.id,
+            preset.id,
+            thumbnail_path,
+            Self::content_type(preset),
+            width,
+            height,
+            size,
+            now,
+            actor_id,
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))
+    }
+
+    /// Renders every preset marked for ingest. Failures are logged and left for the
+    /// thumbnail endpoint to retry lazily.
+    pub async fn generate_ingest_renditions(pool: &PgPool, media: &MediaModel, user: &UserModel) {
+        let presets = match Self::list_ingest_presets(pool).await {
+            Ok(presets) => presets,
+            Err(e) => return eprintln!("Failed to load rendition presets: {}", e),
+        };
+
+        for preset in &presets {
+            if let Err(e) = Self::generate_rendition(pool, media, user, preset).await {
+                eprintln!(
+                    "Failed to generate {} rendition for media {}: {:?}",
+                    preset.name, media.id, e
+                );
+            }
+        }
+    }
+
+    pub async fn generate_thumbnail(
+        media_type: &MediaTypeEnum,
+        filepath: &str,
+        thumbnail_path: &str,
+        max_size: u32,
+        square: bool,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        match media_type {
+            MediaTypeEnum::Photo => {
+                PhotoService::generate_photo_thumbnail(filepath, thumbnail_path, max_size, square)
+                    .await
+            }
+            MediaTypeEnum::Video => {
+                VideoService::generate_video_thumbnail(filepath, thumbnail_path, max_size, square)
+                    .await
+            }
+            MediaTypeEnum::Audio => {
+                AudioService::generate_audio_thumbnail(filepath, thumbnail_path, max_size, square)
+                    .await
+            }
+            MediaTypeEnum::Document => {
+                DocumentService::generate_document_thumbnail(
+                    filepath,
+                    thumbnail_path,
+                    max_size,
+                    square,
+                )
+                .await
+            }
+            MediaTypeEnum::Unknown => Err("No thumbnail for this media type".into()),
+        }
+    }
+
+    /// e.g. `./uploads/{uuid}/thumbnails/grid/{stem}.webp`
+    pub fn thumbnail_path(
+        user: &UserModel,
+        filename: &str,
+        preset: &RenditionPresetModel,
+    ) -> Option<String> {
+        let stem = Path::new(filename).file_stem()?.to_str()?;
+
+        let extension = match preset.format.as_str() {
+            "jpeg" => "jpg",
+            _ => "webp",
+        };
+
+        Some(format!(
+            "./uploads/{}/thumbnails/{}/{}.{}",
+            user.uuid, preset.name, stem, extension
+        ))
+    }
+
+    /// Strong validator for a stored thumbnail. Every regeneration rewrites the row, so
+    /// its update time and size change whenever the bytes may have.
+    pub fn etag(rendition: &MediaRenditionModel) -> String {
+        let version = rendition
+            .updated_at
+            .map(|t| t.timestamp_micros())
+            .unwrap_or_default();
+
+        format!(
+            "\"{}-{}-{}\"",
+            rendition.uuid,
+            version,
+            rendition.size.unwrap_or_default()
+        )
+    }
+
+    pub fn content_type(preset: &RenditionPresetModel) -> &'static str {
+        match preset.format.as_str() {
+            "jpeg" => "image/jpeg",
+            _ => "image/webp",
+        }
+    }
+
+    /// SVG placeholder for media without a renderable preview, e.g. audio without cover
+    /// art or a document that is not a PDF.
+    pub fn icon(media_type: &MediaTypeEnum) -> &'static [u8] {
+        match media_type {
+            MediaTypeEnum::Audio => AUDIO_ICON,
+            MediaTypeEnum::Document => DOCUMENT_ICON,
+            _ => FILE_ICON,
+        }
+    }
+}
@@ -3,16 +3,26 @@ use std::{
     fs::{self, File, OpenOptions},
     io::Write,
 };
+use tokio::sync::Semaphore;
 
+use crate::auth::services::AuthService;
 use crate::errors::app_error::AppError;
 use crate::media::{
     dtos::UploadResponseDto,
     enums::media_type_enum::MediaTypeEnum,
-    models::MediaMetadataModel,
-    services::{FileService, MediaMetadataService, MediaService, PhotoService, VideoService},
+    models::MediaModel,
+    services::{
+        FileService, HlsService, MediaMetadataService, MediaService, MotionPhotoService,
+        PlaybackService, ThumbnailService, VideoPreviewService, XmpService,
+    },
 };
+use crate::stack::services::StackService;
 use crate::user::{models::UserModel, services::UserService};
 
+/// Thumbnails and previews for freshly ingested media are rendered a few at a time, so
+/// a bulk import queues its work instead of decoding every file at once.
+pub static DERIVATIVE_SLOTS: Semaphore = Semaphore::const_new(2);
+
 pub struct UploadService {}
 
 impl UploadService {
@@ -79,29 +89,19 @@ impl UploadService {
             let final_path =
                 Self::assemble_file(user, &file_name, &original_file_name, total_chunks).await?;
 
-            let mime_type = infer::get_from_path(&final_path)
-                .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?
-                .map(|t| t.mime_type().to_string())
-                .unwrap_or_else(|| "application/octet-stream".to_string());
-
-            let media_type = MediaTypeEnum::from_mime(&mime_type) as i32;
-
-            let media = MediaService::create_media(db, user, &file_name, &final_path, media_type)
-                .await
-                .map_err(|e| AppError::InternalServerError(format!("DB error: {}", e)))?;
+            if XmpService::is_sidecar(&original_file_name) {
+                let matched =
+                    XmpService::attach_sidecar(db, user, &final_path, &original_file_name).await?;
 
-            let metadata = Self::extract_metadata(&final_path, &original_file_name).await?;
-            let _media_metadata =
-                MediaMetadataService::create_metadata(db, &media, &metadata).await;
-
-            if mime_type.starts_with("image/") {
-                let _thumbnail =
-                    PhotoService::generate_photo_thumbnail(&final_path, &file_name, 400, user)
-                        .await;
-            } else if mime_type.starts_with("video/") {
-                let _thumbnail =
-                    VideoService::generate_video_thumbnail(&final_path, &file_name, 400, user);
+                return Ok(Json(UploadResponseDto {
+                    success: true,
+                    message: format!("Sidecar applied to {} media", matched),
+                    chunk_received: chunk_number,
+                    file_id: None,
+                }));
             }
+
+            Self::ingest_file(db, user, &file_name, &final_path, &original_file_name).await?;
         }
 
         Ok(Json(UploadResponseDto {
@@ -112,6 +112,90 @@ impl UploadService {
         }))
     }
 
+    /// Registers a file already in the user's upload directory as media: metadata,
+    /// curation, live photo and stack detection, then renditions in the background.
+    pub async fn ingest_file(
+        db: &sqlx::PgPool,
+        user: &UserModel,
+        file_name: &str,
+        final_path: &str,
+        original_file_name: &str,
+    ) -> Result<MediaModel, AppError> {
+        let mime_type = MediaMetadataService::detect_mime_type(final_path, original_file_name)?;
+
+        let media_type = MediaTypeEnum::from_mime(&mime_type);
+
+        let media =
+            MediaService::create_media(db, user, file_name, final_path, media_type.clone() as i32)
+                .await
+                .map_err(|e| AppError::InternalServerError(format!("DB error: {}", e)))?;
+
+        XmpService::claim_pending_sidecar(final_path, original_file_name);
+
+        let metadata =
+            MediaMetadataService::extract_metadata(final_path, original_file_name).await?;
+        let _media_metadata = MediaMetadataService::create_metadata(db, &media, &metadata).await;
+
+        if media_type == MediaTypeEnum::Video && PlaybackService::needs_playback(&metadata) {
+            if let Err(e) = PlaybackService::request_playback(db, user, &media).await {
+                eprintln!("Failed to queue playback transcode: {:?}", e);
+            }
+        }
+
+        if let Err(e) = XmpService::import_curation(db, user.id, &media, &metadata).await {
+            eprintln!("Failed to import XMP curation: {:?}", e);
+        }
+
+        if let Some(content_identifier) = &metadata.content_identifier {
+            if let Err(e) =
+                MotionPhotoService::link_live_photo(db, user.id, content_identifier).await
+            {
+                eprintln!("Failed to link live photo: {}", e);
+            }
+        }
+
+        if let Some(taken_at) = metadata.taken_at {
+            if let Err(e) = StackService::detect_stacks(db, user.id, Some(taken_at)).await {
+                eprintln!("Failed to detect stacks: {}", e);
+            }
+        }
+
+        Self::spawn_derivatives(db, user, &media, metadata.duration);
+
+        Ok(media)
+    }
+
+    /// Renders thumbnails in the background, plus previews and an HLS stream for videos.
+    pub fn spawn_derivatives(
+        db: &sqlx::PgPool,
+        user: &UserModel,
+        media: &MediaModel,
+        duration: Option<f64>,
+    ) {
+        let pool = db.clone();
+        let owner = user.clone();
+        let ingested = media.clone();
+
+        tokio::spawn(AuthService::login(user.clone(), async move {
+            let _slot = DERIVATIVE_SLOTS.acquire().await;
+
+            ThumbnailService::generate_ingest_renditions(&pool, &ingested, &owner).await;
+
+            if ingested.media_type == MediaTypeEnum::Video {
+                let filepath = ingested.filepath.clone();
+
+                let _ = tokio::task::spawn_blocking(move || {
+                    VideoPreviewService::generate_previews(&filepath, duration)
+                })
+                .await;
+
+                if let Err(e) = HlsService::request_stream(&pool, &ingested, &owner).await {
+                    eprintln!("Failed to queue HLS stream: {:?}", e);
+                }
+            }
+        }));
+    }
+
     fn is_upload_complete(temp_dir: &str, total_chunks: usize) -> bool {
         match fs::read_dir(temp_dir) {
             Ok(entries) => entries.count() == total_chunks,
@@ -134,7 +218,7 @@ impl UploadService {
             .truncate(true)
             .write(true)
             .open(&output_path)
-            .map_err(|_| (AppError::InternalServerError("Something went wrong".into())))?;
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
 
         let temp_dir = format!("./uploads/{}/temp/{}", user.uuid, original_file_name);
 
@@ -142,48 +226,15 @@ impl UploadService {
             let chunk_path = format!("{}/chunk_{}", temp_dir, chunk_number);
 
             let chunk_data = fs::read(&chunk_path)
-                .map_err(|_| (AppError::InternalServerError("Something went wrong".into())))?;
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
             output_file
                 .write_all(&chunk_data)
-                .map_err(|_| (AppError::InternalServerError("Something went wrong".into())))?;
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
         }
 
         fs::remove_dir_all(temp_dir)
-            .map_err(|_| (AppError::InternalServerError("Something went wrong".into())))?;
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
 
         Ok(output_path)
     }
-
-    async fn extract_metadata(
-        filepath: &str,
-        original_filename: &str,
-    ) -> Result<MediaMetadataModel, AppError> {
-        let mime_type = infer::get_from_path(filepath)
-            .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?
-            .map(|t| t.mime_type().to_string())
-            .unwrap_or_else(|| "application/octet-stream".to_string());
-
-        let size = std::fs::metadata(filepath)
-            .map_err(|_| AppError::InternalServerError("Something went wrong".to_string()))?
-            .len() as i64;
-
-        let mut metadata = MediaMetadataModel {
-            mime_type: Some(mime_type.to_string()),
-            size: Some(size),
-            original_filename: Some(original_filename.to_string()),
-            ..Default::default()
-        };
-
-        if mime_type.starts_with("image/") {
-            PhotoService::extract_photo_metadata(filepath, &mut metadata);
-        } else if mime_type.starts_with("video/") {
-            VideoService::extract_video_metadata(filepath, &mut metadata);
-        }
-
-        if let Ok(hash) = FileService::generate_file_hash(filepath) {
-            metadata.hash = Some(hash);
-        }
-
-        Ok(metadata)
-    }
 }
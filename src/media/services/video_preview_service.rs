@@ -0,0 +1,206 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::media::services::{FileService, POSTER_DIR, SPRITE_DIR};
+
+const POSTER_CANDIDATE_FRAMES: u32 = 120;
+const POSTER_MAX_OFFSET: f64 = 5.0;
+
+const SPRITE_TILE_WIDTH: u32 = 160;
+const SPRITE_COLUMNS: u32 = 10;
+const SPRITE_MAX_TILES: f64 = 100.0;
+/// Relative to the track, which is served from `/media/{id}/sprite.vtt`.
+const SPRITE_URL: &str = "sprite.jpg";
+
+pub struct VideoPreviewService {}
+
+impl VideoPreviewService {
+    /// Still image shown before playback starts. Starting a little into the video skips
+    /// fade-ins and black leaders, and ffmpeg's `thumbnail` filter then picks the most
+    /// representative of the following frames rather than whichever comes first.
+    pub fn generate_poster(
+        filepath: &str,
+        duration: Option<f64>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let poster_path =
+            FileService::derived_path(filepath, POSTER_DIR, "jpg").ok_or("Invalid filename")?;
+
+        if Path::new(&poster_path).exists() {
+            return Ok(poster_path);
+        }
+
+        if let Some(parent) = Path::new(&poster_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let offset = duration
+            .map(|d| (d * 0.1).min(POSTER_MAX_OFFSET))
+            .unwrap_or_default();
+        let offset = format!("{:.3}", offset);
+        let filter = format!("thumbnail=n={}", POSTER_CANDIDATE_FRAMES);
+        let partial_path = Self::partial_path(&poster_path);
+
+        let status = Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-hide_banner",
+                "-loglevel",
+                "error",
+                "-ss",
+                &offset,
+                "-i",
+                filepath,
+                "-vf",
+                &filter,
+                "-frames:v",
+                "1",
+                "-q:v",
+                "3",
+                &partial_path,
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            let _ = fs::remove_file(&partial_path);
+            return Err("Failed to generate poster frame".into());
+        }
+
+        fs::rename(&partial_path, &poster_path)?;
+
+        Ok(poster_path)
+    }
+
+    /// Sprite sheet of evenly spaced frames plus the WebVTT track that maps each time
+    /// span onto its tile, as players expect for scrub previews. Only keyframes are
+    /// decoded, which keeps long videos cheap at the cost of slightly uneven spacing.
+    pub fn generate_sprite(
+        filepath: &str,
+        duration: Option<f64>,
+    ) -> Result<(String, String), Box<dyn std::error::Error>> {
+        let sprite_path =
+            FileService::derived_path(filepath, SPRITE_DIR, "jpg").ok_or("Invalid filename")?;
+        let track_path =
+            FileService::derived_path(filepath, SPRITE_DIR, "vtt").ok_or("Invalid filename")?;
+
+        if Path::new(&sprite_path).exists() && Path::new(&track_path).exists() {
+            return Ok((sprite_path, track_path));
+        }
+
+        let duration = duration
+            .filter(|d| *d > 0.0)
+            .ok_or("Video has no duration")?;
+
+        if let Some(parent) = Path::new(&sprite_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let interval = (duration / SPRITE_MAX_TILES).ceil().max(1.0);
+        let count = ((duration / interval).ceil() as u32).max(1);
+        let columns = count.min(SPRITE_COLUMNS);
+        let rows = count.div_ceil(SPRITE_COLUMNS);
+
+        let filter = format!(
+            "fps=1/{}, scale={}:-2, tile={}x{}",
+            interval, SPRITE_TILE_WIDTH, columns, rows
+        );
+        let partial_path = Self::partial_path(&sprite_path);
+
+        let status = Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-hide_banner",
+                "-loglevel",
+                "error",
+                "-skip_frame",
+                "nokey",
+                "-i",
+                filepath,
+                "-an",
+                "-vf",
+                &filter,
+                "-frames:v",
+                "1",
+                "-q:v",
+                "4",
+                &partial_path,
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            let _ = fs::remove_file(&partial_path);
+            return Err("Failed to generate sprite sheet".into());
+        }
+
+        // Tiles come out at the video's displayed aspect ratio, which is only known
+        // once ffmpeg has applied any rotation.
+        let (width, height) = image::image_dimensions(&partial_path)?;
+        let (tile_width, tile_height) = (width / columns, height / rows);
+
+        let mut track = String::from("WEBVTT\n");
+
+        for index in 0..count {
+            let start = index as f64 * interval;
+            let end = ((index + 1) as f64 * interval).min(duration);
+
+            write!(
+                track,
+                "\n{} --> {}\n{}#xywh={},{},{},{}\n",
+                Self::timestamp(start),
+                Self::timestamp(end),
+                SPRITE_URL,
+                (index % columns) * tile_width,
+                (index / columns) * tile_height,
+                tile_width,
+                tile_height,
+            )?;
+        }
+
+        fs::write(&track_path, track)?;
+        fs::rename(&partial_path, &sprite_path)?;
+
+        Ok((sprite_path, track_path))
+    }
+
+    /// Renders both previews for a freshly ingested video; failures are left for the
+    /// preview routes to retry.
+    pub fn generate_previews(filepath: &str, duration: Option<f64>) {
+        if let Err(e) = Self::generate_poster(filepath, duration) {
+            eprintln!("Failed to generate poster for {}: {:?}", filepath, e);
+        }
+
+        if let Err(e) = Self::generate_sprite(filepath, duration) {
+            eprintln!("Failed to generate sprite for {}: {:?}", filepath, e);
+        }
+    }
+
+    /// `HH:MM:SS.mmm`, the only form WebVTT accepts for cues past an hour.
+    fn timestamp(seconds: f64) -> String {
+        let millis = (seconds * 1000.0).round() as u64;
+
+        format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            millis / 3_600_000,
+            millis / 60_000 % 60,
+            millis / 1000 % 60,
+            millis % 1000
+        )
+    }
+
+    /// Keeps the extension so ffmpeg still picks the image encoder from it.
+    fn partial_path(path: &str) -> String {
+        match path.rsplit_once('.') {
+            Some((stem, extension)) => format!("{}.partial.{}", stem, extension),
+            None => format!("{}.partial", path),
+        }
+    }
+}
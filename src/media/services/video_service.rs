@@ -4,9 +4,16 @@ use std::{
     path::Path,
     process::{Command, Stdio},
 };
+use tokio::sync::Semaphore;
 
-use crate::media::models::MediaMetadataModel;
-use crate::user::models::UserModel;
+use crate::media::{
+    models::MediaMetadataModel,
+    services::{PerceptualHashService, PlaceholderService, QUICKTIME_CONTENT_IDENTIFIER},
+};
+
+/// Each transcode already keeps every core busy; running them in parallel only makes
+/// all of them late.
+pub static TRANSCODE_SLOTS: Semaphore = Semaphore::const_new(1);
 
 pub struct VideoService {}
 
@@ -26,6 +33,12 @@ impl VideoService {
                             metadata.taken_at = Some(datetime.naive_utc());
                         }
                     }
+
+                    metadata.content_identifier = tags
+                        .extra
+                        .get(QUICKTIME_CONTENT_IDENTIFIER)
+                        .and_then(|v| v.as_str())
+                        .map(|v| v.to_string());
                 }
 
                 for stream in info.streams {
@@ -46,6 +59,15 @@ impl VideoService {
                         _ => {}
                     }
                 }
+
+                // Players and ffmpeg apply the rotation, so a quarter turn shows the
+                // stored frame with its sides swapped.
+                let quarter_turn = Self::read_rotation(path).is_some_and(|r| r % 180 == 90);
+
+                (metadata.display_width, metadata.display_height) = match quarter_turn {
+                    true => (metadata.height, metadata.width),
+                    false => (metadata.width, metadata.height),
+                };
             }
             Err(e) => {
                 eprintln!("Could not analyze file with ffprobe: {:?}", e);
@@ -53,49 +75,156 @@ impl VideoService {
         }
     }
 
+    /// Clockwise degrees, 0-359, the first video stream is turned by for display. Newer
+    /// muxers store it as display matrix side data and older ones as a `rotate` tag,
+    /// neither of which the `ffprobe` crate exposes.
+    pub fn read_rotation(path: &str) -> Option<i32> {
+        let output = Command::new("ffprobe")
+            .args([
+                "-v",
+                "error",
+                "-select_streams",
+                "v:0",
+                "-show_entries",
+                "stream_tags=rotate:stream_side_data=rotation",
+                "-of",
+                "json",
+                path,
+            ])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+
+        let info: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+        let stream = info.get("streams")?.get(0)?;
+
+        // The display matrix turns counter-clockwise, the tag clockwise.
+        let degrees = stream
+            .get("side_data_list")
+            .and_then(|list| list.as_array())
+            .and_then(|list| list.iter().find_map(|data| data.get("rotation")?.as_f64()))
+            .map(|rotation| -rotation)
+            .or_else(|| stream.get("tags")?.get("rotate")?.as_str()?.parse().ok())?;
+
+        Some((degrees.round() as i32).rem_euclid(360))
+    }
+
+    /// WebP output is a short animated loop from the one second mark; JPEG output is a
+    /// single frame from the same point.
     pub async fn generate_video_thumbnail(
         filepath: &str,
-        filename: &str,
-        max_width: u32,
-        user: &UserModel,
+        thumbnail_path: &str,
+        max_size: u32,
+        square: bool,
     ) -> Result<String, Box<dyn std::error::Error>> {
-        let output_path = format!("./uploads/{}/thumbnails", user.uuid);
-        fs::create_dir_all(&output_path)?;
+        if let Some(parent) = Path::new(thumbnail_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let animated = thumbnail_path.ends_with(".webp");
+
+        let scale = Self::thumbnail_scale_filter(max_size, square);
+        let filter = match animated {
+            true => format!("fps=10, {}", scale),
+            false => scale,
+        };
 
-        let stem = Path::new(filename)
-            .file_stem() // gets the filename without extension
-            .and_then(|s| s.to_str())
-            .ok_or("Invalid filename")?;
+        let mut args = vec![
+            "-y",
+            "-hide_banner",
+            "-loglevel",
+            "error",
+            "-ss",
+            "00:00:01",
+        ];
+
+        if animated {
+            args.extend(["-t", "3"]);
+        }
+
+        args.extend(["-i", filepath, "-vf", &filter]);
+
+        match animated {
+            true => args.extend(["-loop", "0"]),
+            false => args.extend(["-frames:v", "1"]),
+        }
 
-        let thumbnail_path = format!("{}/{}.webp", output_path, stem);
+        args.push(thumbnail_path);
 
         let status = Command::new("ffmpeg")
+            .args(&args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            return Err("Failed to generate video thumbnail".into());
+        }
+
+        Ok(thumbnail_path.to_string())
+    }
+
+    /// ffmpeg equivalent of `PhotoService::fit_thumbnail`.
+    pub fn thumbnail_scale_filter(max_size: u32, square: bool) -> String {
+        match square {
+            true => format!(
+                "crop='min(iw,ih)':'min(iw,ih)', scale='min({0},iw)':'min({0},ih)':flags=lanczos",
+                max_size
+            ),
+            false => format!(
+                "scale='min({0},iw)':'min({0},ih)':force_original_aspect_ratio=decrease:flags=lanczos",
+                max_size
+            ),
+        }
+    }
+
+    /// Perceptual hash and BlurHash placeholder of the keyframe the thumbnail starts at.
+    pub fn generate_hashes(filepath: &str) -> (Option<String>, Option<String>) {
+        let frame = match Self::extract_keyframe(filepath) {
+            Ok(frame) => frame,
+            Err(e) => {
+                eprintln!("Could not extract keyframe: {:?}", e);
+                return (None, None);
+            }
+        };
+
+        match image::load_from_memory(&frame) {
+            Ok(img) => (
+                Some(PerceptualHashService::dhash(&img)),
+                PlaceholderService::blurhash(&img),
+            ),
+            Err(_) => (None, None),
+        }
+    }
+
+    /// Decodes the first keyframe after the one second mark as a PNG.
+    pub fn extract_keyframe(filepath: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let output = Command::new("ffmpeg")
             .args([
-                "-y",
                 "-hide_banner",
                 "-loglevel",
                 "error",
                 "-ss",
                 "00:00:01",
-                "-t",
-                "3",
                 "-i",
                 filepath,
-                "-vf",
-                &format!("fps=10, scale={}:-1:flags=lanczos", max_width),
-                "-loop",
-                "0",
-                &thumbnail_path,
+                "-frames:v",
+                "1",
+                "-f",
+                "image2pipe",
+                "-vcodec",
+                "png",
+                "-",
             ])
-            .stdout(Stdio::null())
+            .stdin(Stdio::null())
             .stderr(Stdio::null())
-            .status()?;
+            .output()?;
 
-        if !status.success() {
-            return Err("Failed to generate WebP thumbnail".into());
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err("Failed to extract keyframe".into());
         }
 
-        Ok(thumbnail_path)
+        Ok(output.stdout)
     }
 
     fn parse_ffmpeg_rational(rate: &str) -> Option<f32> {
@@ -0,0 +1,365 @@
+use roxmltree::{Document, Node};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{fs, path::Path};
+
+use crate::errors::app_error::AppError;
+use crate::media::{
+    dtos::MediaBulkUpdatePayloadDto,
+    models::{MediaMetadataModel, MediaModel},
+    services::{FileService, MediaService, ReextractService, SIDECAR_DIR},
+};
+use crate::tag::services::TagService;
+use crate::user::models::UserModel;
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+const DC: &str = "http://purl.org/dc/elements/1.1/";
+const XMP: &str = "http://ns.adobe.com/xap/1.0/";
+const LIGHTROOM: &str = "http://ns.adobe.com/lightroom/1.0/";
+const MWG_REGIONS: &str = "http://www.metadataworkinggroup.com/schemas/regions/";
+const AREA: &str = "http://ns.adobe.com/xmp/sType/Area#";
+/// Sidecars uploaded before their original wait here, under their uploaded name.
+const PENDING_DIR: &str = "pending";
+
+pub struct XmpService {}
+
+impl XmpService {
+    pub fn is_sidecar(filename: &str) -> bool {
+        Path::new(filename)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("xmp"))
+    }
+
+    /// Reads the embedded XMP packet of an image and then its sidecar, so values from a
+    /// DAM's sidecar win over whatever the camera or an earlier export embedded.
+    pub fn extract_xmp_metadata(
+        filepath: &str,
+        mime_type: &str,
+        metadata: &mut MediaMetadataModel,
+    ) {
+        if mime_type.starts_with("image/") {
+            if let Ok(data) = fs::read(filepath) {
+                if let Some(packet) = Self::xmp_packet(&data) {
+                    Self::apply_packet(packet, metadata);
+                }
+            }
+        }
+
+        if let Some(sidecar) = Self::find_sidecar(filepath) {
+            match fs::read_to_string(&sidecar) {
+                Ok(packet) => Self::apply_packet(&packet, metadata),
+                Err(e) => eprintln!("{:?}", e),
+            }
+        }
+    }
+
+    /// Carries the imported rating and keywords over to the media's own curation.
+    pub async fn import_curation(
+        pool: &PgPool,
+        user_id: i32,
+        media: &MediaModel,
+        metadata: &MediaMetadataModel,
+    ) -> Result<(), AppError> {
+        if let Some(rating) = metadata.rating {
+            let payload = MediaBulkUpdatePayloadDto {
+                media_ids: vec![media.id],
+                favorite: None,
+                rating: Some(rating),
+                archived: None,
+            };
+
+            MediaService::bulk_update_media(pool, user_id, &payload)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        for keyword in metadata.keywords.iter().flatten() {
+            let tag = TagService::find_or_create_tag(pool, user_id, keyword)
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+            TagService::attach_media(pool, tag.id, user_id, &[media.id])
+                .await
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Pairs an uploaded sidecar with the most recent upload of its original and
+    /// re-reads that media's metadata. Camera names like `IMG_0001` repeat, so older
+    /// media of the same name are left alone. A sidecar uploaded ahead of its original
+    /// waits until the original is ingested.
+    pub async fn attach_sidecar(
+        pool: &PgPool,
+        user: &UserModel,
+        uploaded_path: &str,
+        original_filename: &str,
+    ) -> Result<usize, AppError> {
+        let clean_filename = FileService::clean_filename(original_filename);
+        let base = &clean_filename[..clean_filename.len() - 4];
+
+        let media = MediaService::find_latest_media_by_original_name(pool, user.id, base)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let (destination, media) = match media {
+            Some(media) => (Self::sidecar_path(&media.filepath), Some(media)),
+            None => (
+                FileService::sidecar_dir(uploaded_path)
+                    .map(|dir| format!("{}/{}/{}", dir, PENDING_DIR, clean_filename)),
+                None,
+            ),
+        };
+
+        let destination = destination
+            .ok_or_else(|| AppError::InternalServerError("Something went wrong".into()))?;
+
+        if let Some(parent) = Path::new(&destination).parent() {
+            fs::create_dir_all(parent)
+                .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+        }
+
+        fs::rename(uploaded_path, &destination)
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        match media {
+            Some(media) => {
+                Self::apply_sidecar(pool, user, &media).await?;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Re-reads the media's metadata now that its sidecar is in place.
+    pub async fn apply_sidecar(
+        pool: &PgPool,
+        user: &UserModel,
+        media: &MediaModel,
+    ) -> Result<(), AppError> {
+        let metadata = ReextractService::reextract_media_metadata(pool, media).await?;
+
+        Self::import_curation(pool, user.id, media, &metadata).await
+    }
+
+    /// Moves a sidecar that was uploaded ahead of this original into place. Sidecars are
+    /// named after the full original (`IMG_1234.CR2.xmp`, darktable) or its stem
+    /// (`IMG_1234.xmp`, Lightroom); the full name wins when both exist.
+    pub fn claim_pending_sidecar(filepath: &str, original_filename: &str) {
+        let (Some(sidecar_dir), Some(destination), Some(stem)) = (
+            FileService::sidecar_dir(filepath),
+            Self::sidecar_path(filepath),
+            Path::new(original_filename)
+                .file_stem()
+                .and_then(|stem| stem.to_str()),
+        ) else {
+            return;
+        };
+
+        let pending_dir = format!("{}/{}", sidecar_dir, PENDING_DIR);
+
+        let Ok(entries) = fs::read_dir(&pending_dir) else {
+            return;
+        };
+
+        let candidates: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| Self::is_sidecar(name))
+            .collect();
+
+        let pending = [
+            FileService::clean_filename(original_filename),
+            FileService::clean_filename(stem),
+        ]
+        .iter()
+        .find_map(|wanted| {
+            candidates
+                .iter()
+                .find(|name| name[..name.len() - 4].eq_ignore_ascii_case(wanted))
+        });
+
+        if let Some(pending) = pending {
+            if let Err(e) = fs::rename(format!("{}/{}", pending_dir, pending), destination) {
+                eprintln!("Failed to claim sidecar {}: {:?}", pending, e);
+            }
+        }
+    }
+
+    /// Each media's sidecar is keyed by its stored file name, which is unique, e.g.
+    /// `./uploads/{uuid}/sidecars/{random prefix}IMG_1234.xmp`.
+    pub fn sidecar_path(filepath: &str) -> Option<String> {
+        FileService::derived_path(filepath, SIDECAR_DIR, "xmp")
+    }
+
+    pub fn find_sidecar(filepath: &str) -> Option<String> {
+        Self::sidecar_path(filepath).filter(|path| Path::new(path).is_file())
+    }
+
+    pub fn xmp_packet(data: &[u8]) -> Option<&str> {
+        let start = data.windows(10).position(|w| w == b"<x:xmpmeta")?;
+        let end = start
+            + data[start..]
+                .windows(12)
+                .position(|w| w == b"</x:xmpmeta>")?
+            + 12;
+
+        std::str::from_utf8(&data[start..end]).ok()
+    }
+
+    fn apply_packet(packet: &str, metadata: &mut MediaMetadataModel) {
+        let doc = match Document::parse(packet) {
+            Ok(doc) => doc,
+            Err(e) => return eprintln!("Could not parse XMP: {:?}", e),
+        };
+
+        let root = doc.root();
+
+        if let Some(title) = Self::lang_alt(root, DC, "title") {
+            metadata.title = Some(title);
+        }
+
+        if let Some(description) = Self::lang_alt(root, DC, "description") {
+            metadata.description = Some(description);
+        }
+
+        let mut keywords = Self::list(root, DC, "subject");
+
+        // digiKam and Lightroom also write "People|Alice" paths; the leaf is the keyword.
+        keywords.extend(
+            Self::list(root, LIGHTROOM, "hierarchicalSubject")
+                .iter()
+                .filter_map(|path| path.rsplit('|').next())
+                .map(|leaf| leaf.trim().to_string()),
+        );
+
+        let mut unique: Vec<String> = Vec::new();
+        for keyword in keywords.into_iter().filter(|k| !k.is_empty()) {
+            if !unique.iter().any(|u| u.eq_ignore_ascii_case(&keyword)) {
+                unique.push(keyword);
+            }
+        }
+
+        if !unique.is_empty() {
+            metadata.keywords = Some(unique);
+        }
+
+        // -1 marks a rejected photo in Lightroom; there is no equivalent here.
+        if let Some(rating) = Self::simple(root, XMP, "Rating")
+            .and_then(|r| r.parse::<f32>().ok())
+            .filter(|r| *r >= 0.0)
+        {
+            metadata.rating = Some(rating.round().min(5.0) as i16);
+        }
+
+        let regions = Self::regions(root);
+
+        if !regions.is_empty() {
+            metadata.regions = Some(serde_json::Value::Array(regions));
+        }
+    }
+
+    /// MWG regions (faces, pets, focus areas). Areas are normalized, centre-based boxes.
+    fn regions(root: Node) -> Vec<serde_json::Value> {
+        let Some(list) = root
+            .descendants()
+            .find(|n| n.has_tag_name((MWG_REGIONS, "RegionList")))
+        else {
+            return Vec::new();
+        };
+
+        list.descendants()
+            .filter(|n| n.has_tag_name((RDF, "li")))
+            .filter_map(|region| {
+                let area = Self::child(region, MWG_REGIONS, "Area")?;
+                let coordinate = |name| Self::field(area, AREA, name)?.parse::<f64>().ok();
+
+                Some(json!({
+                    "name": Self::field(region, MWG_REGIONS, "Name"),
+                    "type": Self::field(region, MWG_REGIONS, "Type"),
+                    "x": coordinate("x")?,
+                    "y": coordinate("y")?,
+                    "w": coordinate("w")?,
+                    "h": coordinate("h")?,
+                    "unit": Self::field(area, AREA, "unit"),
+                }))
+            })
+            .collect()
+    }
+
+    fn simple(root: Node, ns: &str, name: &str) -> Option<String> {
+        root.descendants()
+            .filter(|n| n.has_tag_name((RDF, "Description")))
+            .find_map(|n| n.attribute((ns, name)))
+            .map(|v| v.trim().to_string())
+            .or_else(|| {
+                root.descendants()
+                    .find(|n| n.has_tag_name((ns, name)))
+                    .and_then(Self::text)
+            })
+            .filter(|v| !v.is_empty())
+    }
+
+    fn lang_alt(root: Node, ns: &str, name: &str) -> Option<String> {
+        let property = root.descendants().find(|n| n.has_tag_name((ns, name)))?;
+        let items: Vec<Node> = property
+            .descendants()
+            .filter(|n| n.has_tag_name((RDF, "li")))
+            .collect();
+
+        items
+            .iter()
+            .find(|n| n.attribute((XML, "lang")) == Some("x-default"))
+            .or(items.first())
+            .and_then(|n| Self::text(*n))
+            .or_else(|| Self::text(property))
+    }
+
+    fn list(root: Node, ns: &str, name: &str) -> Vec<String> {
+        root.descendants()
+            .filter(|n| n.has_tag_name((ns, name)))
+            .flat_map(|property| {
+                property
+                    .descendants()
+                    .filter(|n| n.has_tag_name((RDF, "li")))
+            })
+            .filter_map(Self::text)
+            .collect()
+    }
+
+    /// A struct field can be an attribute, a child element, or either of those on an
+    /// `rdf:Description` nested inside the item.
+    fn field(node: Node, ns: &str, name: &str) -> Option<String> {
+        node.attribute((ns, name))
+            .map(|v| v.trim().to_string())
+            .or_else(|| {
+                node.children()
+                    .find(|c| c.has_tag_name((ns, name)))
+                    .and_then(Self::text)
+            })
+            .or_else(|| {
+                node.children()
+                    .find(|c| c.has_tag_name((RDF, "Description")))
+                    .and_then(|d| Self::field(d, ns, name))
+            })
+    }
+
+    fn child<'a, 'input>(node: Node<'a, 'input>, ns: &str, name: &str) -> Option<Node<'a, 'input>> {
+        node.children()
+            .find(|c| c.has_tag_name((ns, name)))
+            .or_else(|| {
+                node.children()
+                    .find(|c| c.has_tag_name((RDF, "Description")))
+                    .and_then(|d| Self::child(d, ns, name))
+            })
+    }
+
+    fn text(node: Node) -> Option<String> {
+        node.text()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+    }
+}
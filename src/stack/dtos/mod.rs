@@ -0,0 +1,9 @@
+pub mod stack_candidate_row_dto;
+pub mod stack_detail_response_dto;
+pub mod stack_payload_dto;
+pub mod stack_primary_payload_dto;
+
+pub use stack_candidate_row_dto::*;
+pub use stack_detail_response_dto::*;
+pub use stack_payload_dto::*;
+pub use stack_primary_payload_dto::*;
@@ -0,0 +1,10 @@
+use chrono::NaiveDateTime;
+
+pub struct StackCandidateRow {
+    pub id: i32,
+    pub stack_id: Option<i32>,
+    pub original_filename: Option<String>,
+    pub mime_type: Option<String>,
+    pub camera_model: Option<String>,
+    pub taken_at: Option<NaiveDateTime>,
+}
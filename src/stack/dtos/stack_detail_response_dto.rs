@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+use crate::media::models::MediaModel;
+use crate::stack::models::StackModel;
+
+#[derive(Serialize)]
+pub struct StackDetailResponseDto {
+    pub stack: StackModel,
+    pub media: Vec<MediaModel>,
+}
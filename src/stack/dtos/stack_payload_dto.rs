@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct StackPayloadDto {
+    pub media_ids: Vec<i32>,
+    pub primary_media_id: Option<i32>,
+}
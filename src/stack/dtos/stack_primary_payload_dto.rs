@@ -0,0 +1,6 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct StackPrimaryPayloadDto {
+    pub primary_media_id: i32,
+}
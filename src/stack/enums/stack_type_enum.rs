@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "stack_type", rename_all = "lowercase")]
+pub enum StackTypeEnum {
+    Manual = 0,
+    Burst = 1,
+    RawJpeg = 2,
+    Edited = 3,
+}
+
+impl From<i32> for StackTypeEnum {
+    fn from(stack_type: i32) -> Self {
+        match stack_type {
+            1 => StackTypeEnum::Burst,
+            2 => StackTypeEnum::RawJpeg,
+            3 => StackTypeEnum::Edited,
+            _ => StackTypeEnum::Manual,
+        }
+    }
+}
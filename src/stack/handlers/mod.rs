@@ -0,0 +1,3 @@
+pub mod stack_handler;
+
+pub use stack_handler::*;
@@ -0,0 +1,147 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::errors::app_error::AppError;
+use crate::job::{enums::job_type_enum::JobTypeEnum, models::JobModel, services::JobService};
+use crate::media::services::MediaService;
+use crate::stack::{
+    dtos::{StackDetailResponseDto, StackPayloadDto, StackPrimaryPayloadDto},
+    enums::stack_type_enum::StackTypeEnum,
+    models::StackModel,
+    services::StackService,
+};
+use crate::user::models::UserModel;
+
+pub async fn create_stack(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Json(payload): Json<StackPayloadDto>,
+) -> Result<Json<StackModel>, AppError> {
+    let mut media_ids = payload.media_ids;
+    media_ids.sort();
+    media_ids.dedup();
+
+    if media_ids.len() < 2 {
+        return Err(AppError::BadRequest(
+            "A stack needs at least two media".into(),
+        ));
+    }
+
+    let primary_media_id = payload.primary_media_id.unwrap_or(media_ids[0]);
+
+    if !media_ids.contains(&primary_media_id) {
+        return Err(AppError::BadRequest(
+            "Primary media must be part of the stack".into(),
+        ));
+    }
+
+    let owned = MediaService::list_user_media(&state.db, user.id, Some(&media_ids))
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    if owned.len() != media_ids.len() {
+        return Err(AppError::NotFound("Media not found".into()));
+    }
+
+    let stacked = StackService::count_stacked_media(&state.db, user.id, &media_ids)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    if stacked > 0 {
+        return Err(AppError::BadRequest(
+            "Media already belongs to a stack".into(),
+        ));
+    }
+
+    let stack = StackService::create_stack(
+        &state.db,
+        user.id,
+        StackTypeEnum::Manual,
+        primary_media_id,
+        &media_ids,
+    )
+    .await
+    .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(stack))
+}
+
+pub async fn get_stack_detail(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+) -> Result<Json<StackDetailResponseDto>, AppError> {
+    let stack = StackService::find_stack(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .ok_or_else(|| AppError::NotFound("Stack not found".into()))?;
+
+    let media = MediaService::list_stack_media(&state.db, stack.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(StackDetailResponseDto { stack, media }))
+}
+
+pub async fn set_stack_primary(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    Json(payload): Json<StackPrimaryPayloadDto>,
+) -> Result<Json<StackModel>, AppError> {
+    let stack = StackService::set_primary_media(&state.db, id, user.id, payload.primary_media_id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    match stack {
+        Some(stack) => Ok(Json(stack)),
+        None => Err(AppError::NotFound("Stack or media not found".into())),
+    }
+}
+
+pub async fn delete_stack(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+) -> Result<Json<StackModel>, AppError> {
+    let stack = StackService::delete_stack(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    match stack {
+        Some(stack) => Ok(Json(stack)),
+        None => Err(AppError::NotFound("Stack not found".into())),
+    }
+}
+
+pub async fn detect_stacks(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+) -> Result<Json<JobModel>, AppError> {
+    let job = JobService::create_job(&state.db, &user, JobTypeEnum::DetectStacks, None)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    let pool = state.db.clone();
+    let job_id = job.id;
+    let user_id = user.id;
+
+    JobService::spawn(state.db.clone(), user, &job, async move {
+        JobService::mark_running(&pool, job_id, 0)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        let stacked = StackService::detect_stacks(&pool, user_id, None)
+            .await
+            .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+        Ok(Some(json!({ "stacked": stacked })))
+    });
+
+    Ok(Json(job))
+}
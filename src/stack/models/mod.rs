@@ -0,0 +1,3 @@
+pub mod stack_model;
+
+pub use stack_model::*;
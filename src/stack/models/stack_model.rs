@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::stack::enums::stack_type_enum::StackTypeEnum;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct StackModel {
+    pub id: i32,
+    pub uuid: Uuid,
+
+    pub user_id: i32,
+    pub primary_media_id: i32,
+    pub stack_type: StackTypeEnum,
+
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub updated_by: Option<i32>,
+}
@@ -0,0 +1,3 @@
+pub mod stack_route;
+
+pub use stack_route::*;
@@ -0,0 +1,29 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::auth::middlewares::auth_middleware;
+use crate::stack::handlers::{
+    create_stack, delete_stack, detect_stacks, get_stack_detail, set_stack_primary,
+};
+
+pub fn stack_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/stacks", post(create_stack))
+        .route("/stacks/detect", post(detect_stacks))
+        .route(
+            "/stacks/{id}",
+            get(get_stack_detail)
+                .patch(set_stack_primary)
+                .delete(delete_stack),
+        )
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state)
+}
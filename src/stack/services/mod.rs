@@ -0,0 +1,3 @@
+pub mod stack_service;
+
+pub use stack_service::*;
@@ -0,0 +1,386 @@
+use chrono::{NaiveDateTime, Timelike, Utc};
+use sqlx::PgPool;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use crate::auth::services::AuthService;
+use crate::media::services::RawService;
+use crate::stack::{
+    dtos::StackCandidateRow, enums::stack_type_enum::StackTypeEnum, models::StackModel,
+};
+
+const EDITED_SUFFIXES: [&str; 6] = ["-edited", "_edited", "-edit", "_edit", "-bearbeitet", "~2"];
+
+/// Largest gap between two consecutive frames that still counts as one burst.
+const BURST_GAP_MILLIS: i64 = 1000;
+
+pub struct StackService {}
+
+impl StackService {
+    pub async fn find_stack(
+        pool: &PgPool,
+        id: i32,
+        user_id: i32,
+    ) -> Result<Option<StackModel>, sqlx::Error> {
+        sqlx::query_as!(
+            StackModel,
+            r#"select * from stacks where deleted_at is null and id = $1 and user_id = $2"#,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn list_stacks(pool: &PgPool, user_id: i32) -> Result<Vec<StackModel>, sqlx::Error> {
+        sqlx::query_as!(
+            StackModel,
+            r#"select * from stacks where deleted_at is null and user_id = $1 order by id"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create_stack(
+        pool: &PgPool,
+        user_id: i32,
+        stack_type: StackTypeEnum,
+        primary_media_id: i32,
+        media_ids: &[i32],
+    ) -> Result<StackModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let mut tx = pool.begin().await?;
+
+        let stack = sqlx::query_as!(
+            StackModel,
+            r#"insert into stacks (user_id, primary_media_id, stack_type, created_at, updated_at, created_by, updated_by) values ($1, $2, $3, $4, $4, $5, $5) returning *"#,
+            user_id,
+            primary_media_id,
+            stack_type as i32,
+            now,
+            actor_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"update media set stack_id = $1, updated_at = $2, updated_by = $3 where deleted_at is null and user_id = $4 and id = any($5)"#,
+            stack.id,
+            now,
+            actor_id,
+            user_id,
+            media_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(stack)
+    }
+
+    pub async fn count_stacked_media(
+        pool: &PgPool,
+        user_id: i32,
+        media_ids: &[i32],
+    ) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar!(
+            r#"select count(*) from media where deleted_at is null and user_id = $1 and id = any($2) and stack_id is not null"#,
+            user_id,
+            media_ids
+        )
+        .fetch_one(pool)
+        .await?
+        .unwrap_or(0);
+
+        Ok(count)
+    }
+
+    pub async fn add_to_stack(
+        pool: &PgPool,
+        stack_id: i32,
+        user_id: i32,
+        media_ids: &[i32],
+    ) -> Result<u64, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"update media set stack_id = $1, updated_at = $2, updated_by = $3 where deleted_at is null and user_id = $4 and id = any($5)"#,
+            stack_id,
+            now,
+            actor_id,
+            user_id,
+            media_ids
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn set_primary_media(
+        pool: &PgPool,
+        stack_id: i32,
+        user_id: i32,
+        primary_media_id: i32,
+    ) -> Result<Option<StackModel>, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            StackModel,
+            r#"
+                update stacks set primary_media_id = $1, updated_at = $2, updated_by = $3
+                where deleted_at is null and id = $4 and user_id = $5
+                and exists (select 1 from media where id = $1 and stack_id = $4 and deleted_at is null)
+                returning *
+            "#,
+            primary_media_id,
+            now,
+            actor_id,
+            stack_id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn delete_stack(
+        pool: &PgPool,
+        stack_id: i32,
+        user_id: i32,
+    ) -> Result<Option<StackModel>, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let mut tx = pool.begin().await?;
+
+        let stack = sqlx::query_as!(
+            StackModel,
+            r#"update stacks set deleted_at = $1, updated_at = $1, updated_by = $2 where deleted_at is null and id = $3 and user_id = $4 returning *"#,
+            now,
+            actor_id,
+            stack_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if stack.is_some() {
+            sqlx::query!(
+                r#"update media set stack_id = null, updated_at = $1, updated_by = $2 where stack_id = $3"#,
+                now,
+                actor_id,
+                stack_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(stack)
+    }
+
+    /// Finds RAW+JPEG pairs, edited copies and camera bursts among the user's photos and
+    /// stacks them, extending an existing stack when a new member turns up later. When
+    /// `around` is set only media taken within a minute of it are considered.
+    pub async fn detect_stacks(
+        pool: &PgPool,
+        user_id: i32,
+        around: Option<NaiveDateTime>,
+    ) -> Result<usize, sqlx::Error> {
+        let window = chrono::Duration::minutes(1);
+        let from = around.map(|t| t - window);
+        let to = around.map(|t| t + window);
+
+        let candidates = sqlx::query_as!(
+            StackCandidateRow,
+            r#"
+                select a.id, a.stack_id, b.original_filename, b.mime_type, b.camera_model, b.taken_at
+                from media a join media_metadata b on a.id = b.media_id
+                where a.deleted_at is null and b.deleted_at is null and a.user_id = $1 and b.taken_at is not null
+                and ($2::timestamp is null or b.taken_at between $2 and $3)
+                order by b.taken_at, a.id
+            "#,
+            user_id,
+            from,
+            to,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut stacked = 0;
+
+        for (stack_type, group) in Self::group_candidates(&candidates) {
+            let existing: Vec<i32> = {
+                let mut ids: Vec<i32> = group.iter().filter_map(|c| c.stack_id).collect();
+                ids.sort();
+                ids.dedup();
+                ids
+            };
+
+            let loose: Vec<i32> = group
+                .iter()
+                .filter(|c| c.stack_id.is_none())
+                .map(|c| c.id)
+                .collect();
+
+            if loose.is_empty() {
+                continue;
+            }
+
+            match existing.as_slice() {
+                [] => {
+                    let primary = Self::pick_primary(&stack_type, &group);
+                    let ids: Vec<i32> = group.iter().map(|c| c.id).collect();
+
+                    Self::create_stack(pool, user_id, stack_type, primary, &ids).await?;
+                    stacked += ids.len();
+                }
+                [stack_id] => {
+                    stacked += Self::add_to_stack(pool, *stack_id, user_id, &loose).await? as usize;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(stacked)
+    }
+
+    fn group_candidates(
+        candidates: &[StackCandidateRow],
+    ) -> Vec<(StackTypeEnum, Vec<&StackCandidateRow>)> {
+        let mut groups = Vec::new();
+        let mut paired: HashSet<i32> = HashSet::new();
+
+        let mut by_stem: HashMap<(String, NaiveDateTime), Vec<&StackCandidateRow>> = HashMap::new();
+
+        // Only stills: a Live Photo's MOV shares the stem and second of its HEIC.
+        for candidate in candidates.iter().filter(|c| Self::is_image(c)) {
+            if let (Some(stem), Some(taken_at)) = (Self::base_stem(candidate), candidate.taken_at) {
+                let second = taken_at.with_nanosecond(0).unwrap_or(taken_at);
+                by_stem.entry((stem, second)).or_default().push(candidate);
+            }
+        }
+
+        for group in by_stem.into_values().filter(|g| g.len() > 1) {
+            let stack_type = if group.iter().any(|c| Self::is_raw(c)) {
+                StackTypeEnum::RawJpeg
+            } else {
+                StackTypeEnum::Edited
+            };
+
+            paired.extend(group.iter().map(|c| c.id));
+            groups.push((stack_type, group));
+        }
+
+        let mut burst: Vec<&StackCandidateRow> = Vec::new();
+
+        let burst_candidates = candidates
+            .iter()
+            .filter(|c| !paired.contains(&c.id) && Self::is_image(c));
+
+        for candidate in burst_candidates {
+            let continues = burst.last().is_some_and(|last| {
+                last.camera_model == candidate.camera_model
+                    && Self::has_sub_second(last)
+                    && Self::has_sub_second(candidate)
+                    && match (last.taken_at, candidate.taken_at) {
+                        (Some(a), Some(b)) => (b - a).num_milliseconds() <= BURST_GAP_MILLIS,
+                        _ => false,
+                    }
+            });
+
+            if !continues {
+                let finished = std::mem::take(&mut burst);
+
+                if finished.len() > 1 {
+                    groups.push((StackTypeEnum::Burst, finished));
+                }
+            }
+
+            burst.push(candidate);
+        }
+
+        if burst.len() > 1 {
+            groups.push((StackTypeEnum::Burst, burst));
+        }
+
+        groups
+    }
+
+    /// JPEG over RAW so the stack cover is browser-viewable, the edited copy over the
+    /// original, and the first frame of a burst.
+    fn pick_primary(stack_type: &StackTypeEnum, group: &[&StackCandidateRow]) -> i32 {
+        let pick = match stack_type {
+            StackTypeEnum::RawJpeg => group.iter().find(|c| !Self::is_raw(c)),
+            StackTypeEnum::Edited => group.iter().find(|c| Self::is_edited(c)),
+            _ => None,
+        };
+
+        pick.or(group.first()).map(|c| c.id).unwrap_or_default()
+    }
+
+    fn is_image(candidate: &StackCandidateRow) -> bool {
+        candidate
+            .mime_type
+            .as_deref()
+            .is_some_and(|m| m.starts_with("image/"))
+    }
+
+    fn is_raw(candidate: &StackCandidateRow) -> bool {
+        candidate
+            .original_filename
+            .as_deref()
+            .is_some_and(RawService::is_raw_file)
+    }
+
+    fn stem(candidate: &StackCandidateRow) -> Option<String> {
+        candidate
+            .original_filename
+            .as_deref()
+            .and_then(|f| Path::new(f).file_stem())
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_lowercase())
+    }
+
+    fn is_edited(candidate: &StackCandidateRow) -> bool {
+        Self::stem(candidate).is_some_and(|s| Self::strip_edit_suffix(&s) != s)
+    }
+
+    fn base_stem(candidate: &StackCandidateRow) -> Option<String> {
+        Self::stem(candidate).map(|s| Self::strip_edit_suffix(&s))
+    }
+
+    /// "img_1234-edited", "img_1234 (1)" and "img_1234" all share the base "img_1234".
+    fn strip_edit_suffix(stem: &str) -> String {
+        let mut stem = stem.trim();
+
+        if let Some(open) = stem.rfind(" (") {
+            if stem.ends_with(')') && stem[open + 2..stem.len() - 1].parse::<u32>().is_ok() {
+                stem = &stem[..open];
+            }
+        }
+
+        for suffix in EDITED_SUFFIXES {
+            if let Some(base) = stem.strip_suffix(suffix) {
+                return base.to_string();
+            }
+        }
+
+        stem.to_string()
+    }
+
+    fn has_sub_second(candidate: &StackCandidateRow) -> bool {
+        candidate
+            .taken_at
+            .is_some_and(|t| t.and_utc().timestamp_subsec_nanos() != 0)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod tag_media_payload_dto;
+pub mod tag_media_response_dto;
+pub mod tag_payload_dto;
+
+pub use tag_media_payload_dto::*;
+pub use tag_media_response_dto::*;
+pub use tag_payload_dto::*;
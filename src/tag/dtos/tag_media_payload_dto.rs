@@ -0,0 +1,6 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct TagMediaPayloadDto {
+    pub media_ids: Vec<i32>,
+}
@@ -0,0 +1,7 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct TagMediaResponseDto {
+    pub tag_id: i32,
+    pub affected: u64,
+}
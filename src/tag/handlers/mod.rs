@@ -0,0 +1,3 @@
+pub mod tag_handler;
+
+pub use tag_handler::*;
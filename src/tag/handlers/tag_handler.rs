@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::errors::{app_error::AppError, database_error::is_unique_violation};
+use crate::tag::{
+    dtos::{TagMediaPayloadDto, TagMediaResponseDto, TagPayloadDto},
+    models::TagModel,
+    services::TagService,
+};
+use crate::user::models::UserModel;
+
+pub async fn get_tag_list(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+) -> Result<Json<Vec<TagModel>>, AppError> {
+    let tags = TagService::list_tags(&state.db, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(tags))
+}
+
+pub async fn create_tag(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Json(payload): Json<TagPayloadDto>,
+) -> Result<Json<TagModel>, AppError> {
+    let name = validate_tag_name(&payload.name)?;
+
+    let tag = TagService::create_tag(&state.db, user.id, name)
+        .await
+        .map_err(|e| match is_unique_violation(&e) {
+            true => AppError::BadRequest("Tag already exists".into()),
+            false => AppError::InternalServerError("Something went wrong".into()),
+        })?;
+
+    Ok(Json(tag))
+}
+
+pub async fn rename_tag(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    Json(payload): Json<TagPayloadDto>,
+) -> Result<Json<TagModel>, AppError> {
+    let name = validate_tag_name(&payload.name)?;
+
+    let tag = TagService::rename_tag(&state.db, id, user.id, name)
+        .await
+        .map_err(|e| match is_unique_violation(&e) {
+            true => AppError::BadRequest("Tag already exists".into()),
+            false => AppError::InternalServerError("Something went wrong".into()),
+        })?;
+
+    match tag {
+        Some(tag) => Ok(Json(tag)),
+        None => Err(AppError::NotFound("Tag not found".into())),
+    }
+}
+
+pub async fn delete_tag(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+) -> Result<Json<TagModel>, AppError> {
+    let tag = TagService::delete_tag(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    match tag {
+        Some(tag) => Ok(Json(tag)),
+        None => Err(AppError::NotFound("Tag not found".into())),
+    }
+}
+
+pub async fn attach_tag_media(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    Json(payload): Json<TagMediaPayloadDto>,
+) -> Result<Json<TagMediaResponseDto>, AppError> {
+    let tag = find_user_tag(&state, id, &user).await?;
+
+    let affected = TagService::attach_media(&state.db, tag.id, user.id, &payload.media_ids)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(TagMediaResponseDto {
+        tag_id: tag.id,
+        affected,
+    }))
+}
+
+pub async fn detach_tag_media(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<UserModel>,
+    Path(id): Path<i32>,
+    Json(payload): Json<TagMediaPayloadDto>,
+) -> Result<Json<TagMediaResponseDto>, AppError> {
+    let tag = find_user_tag(&state, id, &user).await?;
+
+    let affected = TagService::detach_media(&state.db, tag.id, user.id, &payload.media_ids)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?;
+
+    Ok(Json(TagMediaResponseDto {
+        tag_id: tag.id,
+        affected,
+    }))
+}
+
+async fn find_user_tag(state: &AppState, id: i32, user: &UserModel) -> Result<TagModel, AppError> {
+    TagService::find_tag(&state.db, id, user.id)
+        .await
+        .map_err(|_| AppError::InternalServerError("Something went wrong".into()))?
+        .ok_or_else(|| AppError::NotFound("Tag not found".into()))
+}
+
+fn validate_tag_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Tag name is required".into()));
+    }
+
+    Ok(name)
+}
@@ -0,0 +1,5 @@
+pub mod dtos;
+pub mod handlers;
+pub mod models;
+pub mod routes;
+pub mod services;
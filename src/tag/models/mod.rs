@@ -0,0 +1,3 @@
+pub mod tag_model;
+
+pub use tag_model::*;
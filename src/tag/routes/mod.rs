@@ -0,0 +1,3 @@
+pub mod tag_route;
+
+pub use tag_route::*;
@@ -0,0 +1,27 @@
+use axum::{
+    middleware,
+    routing::{get, patch, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::app::AppState;
+use crate::auth::middlewares::auth_middleware;
+use crate::tag::handlers::{
+    attach_tag_media, create_tag, delete_tag, detach_tag_media, get_tag_list, rename_tag,
+};
+
+pub fn tag_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/tags", get(get_tag_list).post(create_tag))
+        .route("/tags/{id}", patch(rename_tag).delete(delete_tag))
+        .route(
+            "/tags/{id}/media",
+            post(attach_tag_media).delete(detach_tag_media),
+        )
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ))
+        .with_state(app_state)
+}
@@ -0,0 +1,3 @@
+pub mod tag_service;
+
+pub use tag_service::*;
@@ -0,0 +1,205 @@
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::auth::services::AuthService;
+use crate::errors::database_error::is_unique_violation;
+use crate::tag::models::TagModel;
+
+pub struct TagService {}
+
+impl TagService {
+    pub async fn create_tag(
+        pool: &PgPool,
+        user_id: i32,
+        name: &str,
+    ) -> Result<TagModel, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let tag = sqlx::query_as!(
+            TagModel,
+            r#"insert into tags (user_id, name, created_at, updated_at, created_by, updated_by) values ($1, $2, $3, $3, $4, $4) returning *"#,
+            user_id,
+            name,
+            now,
+            actor_id,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(tag)
+    }
+
+    /// Looks a tag up by case-insensitive name, creating it when missing. Used by
+    /// imports, where two files can race to create the same keyword.
+    pub async fn find_or_create_tag(
+        pool: &PgPool,
+        user_id: i32,
+        name: &str,
+    ) -> Result<TagModel, sqlx::Error> {
+        let existing = sqlx::query_as!(
+            TagModel,
+            r#"select * from tags where deleted_at is null and user_id = $1 and lower(name) = lower($2)"#,
+            user_id,
+            name
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(tag) = existing {
+            return Ok(tag);
+        }
+
+        match Self::create_tag(pool, user_id, name).await {
+            Err(e) if is_unique_violation(&e) => {
+                sqlx::query_as!(
+                    TagModel,
+                    r#"select * from tags where deleted_at is null and user_id = $1 and lower(name) = lower($2)"#,
+                    user_id,
+                    name
+                )
+                .fetch_one(pool)
+                .await
+            }
+            result => result,
+        }
+    }
+
+    pub async fn list_tags(pool: &PgPool, user_id: i32) -> Result<Vec<TagModel>, sqlx::Error> {
+        sqlx::query_as!(
+            TagModel,
+            r#"select * from tags where deleted_at is null and user_id = $1 order by lower(name)"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_tag(
+        pool: &PgPool,
+        id: i32,
+        user_id: i32,
+    ) -> Result<Option<TagModel>, sqlx::Error> {
+        sqlx::query_as!(
+            TagModel,
+            r#"select * from tags where deleted_at is null and id = $1 and user_id = $2"#,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn rename_tag(
+        pool: &PgPool,
+        id: i32,
+        user_id: i32,
+        name: &str,
+    ) -> Result<Option<TagModel>, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        sqlx::query_as!(
+            TagModel,
+            r#"update tags set name = $1, updated_at = $2, updated_by = $3 where deleted_at is null and id = $4 and user_id = $5 returning *"#,
+            name,
+            now,
+            actor_id,
+            id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn delete_tag(
+        pool: &PgPool,
+        id: i32,
+        user_id: i32,
+    ) -> Result<Option<TagModel>, sqlx::Error> {
+        let actor_id = AuthService::id();
+        let now = Utc::now();
+
+        let mut tx = pool.begin().await?;
+
+        let tag = sqlx::query_as!(
+            TagModel,
+            r#"update tags set deleted_at = $1, updated_at = $1, updated_by = $2 where deleted_at is null and id = $3 and user_id = $4 returning *"#,
+            now,
+            actor_id,
+            id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if tag.is_some() {
+            sqlx::query!(r#"delete from media_tags where tag_id = $1"#, id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(tag)
+    }
+
+    pub async fn attach_media(
+        pool: &PgPool,
+        tag_id: i32,
+        user_id: i32,
+        media_ids: &[i32],
+    ) -> Result<u64, sqlx::Error> {
+        let actor_id = AuthService::id();
+
+        let result = sqlx::query!(
+            r#"
+                insert into media_tags (media_id, tag_id, created_by)
+                select id, $1, $2 from media where deleted_at is null and user_id = $3 and id = any($4)
+                on conflict do nothing
+            "#,
+            tag_id,
+            actor_id,
+            user_id,
+            media_ids
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn detach_media(
+        pool: &PgPool,
+        tag_id: i32,
+        user_id: i32,
+        media_ids: &[i32],
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+                delete from media_tags
+                where tag_id = $1 and media_id in (select id from media where user_id = $2 and id = any($3))
+            "#,
+            tag_id,
+            user_id,
+            media_ids
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn tags_for_media(
+        pool: &PgPool,
+        media_id: i32,
+    ) -> Result<Vec<TagModel>, sqlx::Error> {
+        sqlx::query_as!(
+            TagModel,
+            r#"select a.* from tags a join media_tags b on a.id = b.tag_id where a.deleted_at is null and b.media_id = $1 order by lower(a.name)"#,
+            media_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Deserializer};
+
+/// Distinguishes a missing field (`None`) from an explicit `null` (`Some(None)`).
+/// Use together with `#[serde(default)]`.
+pub fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}